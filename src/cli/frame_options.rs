@@ -2,54 +2,387 @@
 
 use crate::config::FrameConfig;
 use crate::errors::OliframeError;
-use crate::geometry::{AspectRatio, Margins, RelativePosition};
+use crate::frame::{
+    Antialias, Background, Blending, Caption, Fill, KeepMetadata, Keyline, Mask, Mat, Resampling,
+    Sensitive, Shadow, Template, Texture, TextureFit, Transparency, Watermark, WatermarkArea,
+};
+use crate::geometry::{
+    AspectRatio, AspectRatioMode, CornerRadius, CornerShape, FocusPoint, Length, MarginBasis,
+    Margins, RelativePosition, Side, Size,
+};
 use clap::Args;
 use csscolorparser::Color;
 use image::Rgba;
+use std::path::PathBuf;
 use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 /// CLI options related to the style of the frame around the image.
 #[derive(Args, Debug)]
 pub struct FrameOptions {
+    /// How pixels along rounded edges are smoothed: "analytic" (exact coverage)
+    /// or a supersampling grid from "1x" to "16x" (e.g.: "4x" averages 4×4 samples per pixel).
+    #[arg(long, value_name = "QUALITY", default_value = "analytic")]
+    antialias: String,
+
     /// Fix the final aspect ratio of the output image.
-    /// Specify the ratio as a fraction (e.g.: "16:9") or a decimal (e.g.: "1.777").
+    /// Specify the ratio as a fraction (e.g.: "16:9"), a decimal (e.g.: "1.777") or a preset:
+    /// "instagram-portrait" (4:5), "story" (9:16), "a4", "letter", "golden", "square" or "35mm" (3:2).
+    /// End the ratio with "~" (e.g.: "4:5~") to turn it to the orientation of each image.
     #[arg(long = "ar", value_name = "RATIO", help_heading = "Framing Options")]
     aspect_ratio: Option<String>,
 
-    /// Color of the border/background.  Specify any valid CSS color.
+    /// How the output image is brought to the --ar: "pad" grows the frame on two sides,
+    /// "crop" crops the image so the margins keep their thickness.
+    #[arg(
+        long = "ar-mode",
+        value_name = "MODE",
+        default_value = "pad",
+        requires = "aspect_ratio"
+    )]
+    aspect_ratio_mode: String,
+
+    /// Point of the image kept in view when it is cropped by "--ar-mode crop", which it requires,
+    /// as percentages of its width and height (e.g.: "30,60").
+    /// By default, the crop is anchored at the --position.
+    #[arg(long, value_name = "X,Y", requires = "aspect_ratio")]
+    focus: Option<String>,
+
+    /// How to fill the frame around the image: "color" or "blur".
+    /// "blur" fills the frame with a scaled-up, blurred copy of the image.
+    #[arg(short = 'b', long, value_name = "MODE", default_value = "color")]
+    background: String,
+
+    /// Image file used to fill the frame around the image, such as a paper texture.
+    #[arg(long, value_name = "FILE", conflicts_with = "background")]
+    background_image: Option<PathBuf>,
+
+    /// How the background image is fitted to the frame: "tile", "stretch", "cover" or "center".
+    #[arg(
+        long,
+        value_name = "FIT",
+        default_value = "cover",
+        requires = "background_image"
+    )]
+    background_fit: String,
+
+    /// Color space in which colors are blended: "srgb", or "linear" for gamma-correct blending
+    /// of edges, shadows and gradients.
+    #[arg(long, value_name = "SPACE", default_value = "srgb")]
+    blending: String,
+
+    /// Blur strength of the "blur" background, as a percentage of (width + height) / 2.
+    #[arg(long, value_name = "SIZE", default_value_t = 5.)]
+    blur_radius: f32,
+
+    /// Percentage by which the "blur" background is tinted toward the frame color.
+    #[arg(long, value_name = "AMOUNT", default_value_t = 20.)]
+    dim: f32,
+
+    /// Render a line of text in the frame margin, such as a title or credit.
+    /// The text is a template filled from each image's EXIF data: "{model}" inserts a field,
+    /// "{lens|model|\"unknown\"}" falls back to the next field or a quoted text, and a section
+    /// in brackets (e.g.: "[ · ISO {iso}]") is left out when any of its fields are missing.
+    /// Fields: make, model, lens, fnumber, exposure, iso, focal, focal35, date, time, artist,
+    /// copyright, software, filename.
+    #[arg(long, value_name = "TEXT", requires = "caption_font")]
+    caption: Option<String>,
+
+    /// TrueType or OpenType font file used to render the caption.
+    #[arg(long, value_name = "FILE", requires = "caption")]
+    caption_font: Option<PathBuf>,
+
+    /// Height of the caption text, in pixels (e.g.: "24px") or as a percentage of the
+    /// thickness of its margin (e.g.: "40%").
+    #[arg(long, value_name = "SIZE", default_value = "40%", requires = "caption")]
+    caption_size: String,
+
+    /// Color of the caption text.  Specify any valid CSS color.
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value = "black",
+        requires = "caption"
+    )]
+    caption_color: String,
+
+    /// Alignment of the caption within its margin, in the same format as --position.
+    #[arg(
+        long,
+        value_name = "POSITION",
+        default_value = "center",
+        requires = "caption"
+    )]
+    caption_position: String,
+
+    /// Margin in which the caption is rendered: "top", "right", "bottom" or "left".
+    /// Captions in the left and right margins run along the image.
+    #[arg(
+        long,
+        value_name = "SIDE",
+        default_value = "bottom",
+        requires = "caption"
+    )]
+    caption_margin: String,
+
+    /// Add room for text sized in pixels to the caption's margin, instead of scaling the text down.
+    #[arg(long, requires = "caption")]
+    caption_grow: bool,
+
+    /// Color of the border/background.  Specify any valid CSS color,
+    /// or a CSS "linear-gradient(...)" or "radial-gradient(...)".
     #[arg(short = 'c', long, value_name = "COLOR", default_value = "white")]
     color: String,
 
     /// Add rounded corners to the image.
+    /// Specify one to four values (top-left, top-right, bottom-right, bottom-left), separated by commas,
+    /// in pixels (e.g.: "20") or as a percentage of the shorter side (e.g.: "5%").
     #[arg(short = 'r', long = "radius", value_name = "RADIUS")]
-    corner_radius: Option<u32>,
+    corner_radius: Option<String>,
+
+    /// Round the corners of the whole output image, leaving the area outside transparent.
+    /// Specify the radii as in --radius, with percentages of the output's shorter side.
+    #[arg(long, value_name = "RADIUS")]
+    canvas_radius: Option<String>,
+
+    /// Shape of the rounded corners: "circle", "squircle" (optionally with an exponent,
+    /// e.g.: "squircle:4"; 5 by default) or "chamfer" (a straight cut).
+    #[arg(long, value_name = "SHAPE", default_value = "circle")]
+    corner_shape: String,
+
+    /// Draw a thin stroke around the image, like a gallery mat keyline.
+    /// Specify the width in pixels (e.g.: "2px") or as a percentage of (width + height) / 2 (e.g.: "0.2%").
+    #[arg(long, value_name = "WIDTH")]
+    keyline: Option<String>,
+
+    /// Color of the keyline.  Specify any valid CSS color.
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value = "black",
+        requires = "keyline"
+    )]
+    keyline_color: String,
 
-    /// Relative margins around the image, as a percentage of (width + height) / 2.
-    /// Specify one to four values, separated by commas, in CSS order.
+    /// Space between the image and the keyline, in pixels or as a percentage.
+    #[arg(long, value_name = "GAP", default_value = "0", requires = "keyline")]
+    keyline_gap: String,
+
+    /// Margins around the image, in pixels (e.g.: "40px"), as a percentage of the --margin-basis
+    /// (e.g.: "5%"), or in "mm" or "in" at the resolution given at the end (e.g.: "5mm@300dpi").
+    /// Specify one to four values, separated by commas, in CSS order. For compatibility, values
+    /// without a unit are read as percentages, or as fractions when not greater than 1, with a warning.
     #[arg(short = 'm', long, value_name = "SIZE(S)")]
     margins: Option<String>,
 
+    /// Dimension that percentage margins and mats are measured against: the image's "short" side,
+    /// "long" side, "width", "height", the "average" of its width and height, its "diagonal",
+    /// or the average of the width and height of the "output" image, mats and margins included.
+    #[arg(long, value_name = "BASIS", default_value = "average")]
+    margin_basis: String,
+
+    /// Shape of the window through which the image is seen: "rectangle", "circle", "ellipse"
+    /// or a regular polygon with the given number of sides, up to 64 (e.g.: "polygon:6").
+    #[arg(long, value_name = "SHAPE", default_value = "rectangle")]
+    mask: String,
+
+    /// Image file whose alpha channel shapes the window through which the image is seen.
+    #[arg(long, value_name = "FILE", conflicts_with = "mask")]
+    mask_image: Option<PathBuf>,
+
+    /// Add a mat (a nested border of its own color) between the image and the frame.
+    /// Specify the mat as "SIZE(S):COLOR", with sizes as in --margins (e.g.: "2%:black").
+    /// Repeat the option to stack mats, listed from the image outward.
+    #[arg(long = "mat", value_name = "SIZE(S):COLOR")]
+    mats: Vec<String>,
+
+    /// Color used in place of transparency for formats without an alpha channel (e.g.: JPEG).
+    #[arg(long, value_name = "COLOR", default_value = "white")]
+    matte: String,
+
+    /// Metadata carried over from each input image to its output: "all", "none",
+    /// or a comma-separated list of "exif", "xmp" and "icc" (e.g.: "exif,icc").
+    /// Pixel dimensions are updated to the output size, and the orientation is reset.
+    #[arg(long, value_name = "KINDS", default_value = "all")]
+    metadata: String,
+
     /// Relative position of the input image within the output image.
     /// Horizontal values: "left", "center", "right"
     /// Vertical values: "top", "center", "bottom"
     #[arg(short = 'P', long, value_name = "POSITION")]
     position: Option<String>,
+
+    /// Add a drop shadow behind the image, in pixels, in CSS "box-shadow" order:
+    /// "OFFSET_X OFFSET_Y [BLUR [SPREAD]]".  The frame grows as needed to fit the shadow.
+    #[arg(long, value_name = "SHADOW", allow_hyphen_values = true)]
+    shadow: Option<String>,
+
+    /// Color of the drop shadow.  Specify any valid CSS color.
+    #[arg(
+        long,
+        value_name = "COLOR",
+        default_value = "black",
+        requires = "shadow"
+    )]
+    shadow_color: String,
+
+    /// Opacity of the drop shadow, as a percentage.
+    #[arg(
+        long,
+        value_name = "OPACITY",
+        default_value_t = 50.,
+        requires = "shadow"
+    )]
+    shadow_opacity: f32,
+
+    /// Exact size of the output image, in pixels (e.g.: "1080x1350").
+    /// The image is resized so that it fits along with its frame,
+    /// and the remaining space is distributed according to --position.
+    #[arg(long, value_name = "WxH", conflicts_with = "aspect_ratio")]
+    size: Option<String>,
+
+    /// Filter used to resize the image to the --size: "nearest", "linear", "cubic", "gaussian"
+    /// or "lanczos".
+    #[arg(
+        long,
+        value_name = "FILTER",
+        default_value = "lanczos",
+        requires = "size"
+    )]
+    resample: String,
+
+    /// Strip sensitive metadata from the output, keeping everything else: a comma-separated list
+    /// of "gps" (location), "serial" (camera and lens serial numbers, and maker notes)
+    /// and "owner" (camera owner name), or "privacy" for all of them.
+    #[arg(long, value_name = "GROUPS")]
+    strip: Option<String>,
+
+    /// What happens to transparent pixels of the input image: "flatten" shows the frame
+    /// through them, "keep" leaves them transparent in formats with an alpha channel.
+    #[arg(long, value_name = "MODE", default_value = "flatten")]
+    transparency: String,
+
+    /// Image file, such as a logo, placed over the frame or the image.
+    #[arg(long, value_name = "FILE")]
+    watermark: Option<PathBuf>,
+
+    /// Height of the watermark, in pixels (e.g.: "40px") or as a percentage of the thickness of
+    /// the margin it is anchored in ("frame" area) or of the image's shorter side ("image" area).
+    #[arg(
+        long,
+        value_name = "SIZE",
+        default_value = "50%",
+        requires = "watermark"
+    )]
+    watermark_size: String,
+
+    /// Opacity of the watermark, as a percentage.
+    #[arg(
+        long,
+        value_name = "OPACITY",
+        default_value_t = 100.,
+        requires = "watermark"
+    )]
+    watermark_opacity: f32,
+
+    /// Space between the watermark and the edges of its area, in pixels or as a percentage,
+    /// as in --watermark-size.
+    #[arg(
+        long,
+        value_name = "SIZE",
+        default_value = "25%",
+        requires = "watermark"
+    )]
+    watermark_margin: String,
+
+    /// Where the watermark is anchored within its area, in the same format as --position.
+    #[arg(
+        long,
+        value_name = "POSITION",
+        default_value = "bottom right",
+        requires = "watermark"
+    )]
+    watermark_position: String,
+
+    /// Area the watermark is pinned to: "frame" (the whole output image) or "image".
+    #[arg(
+        long,
+        value_name = "AREA",
+        default_value = "frame",
+        requires = "watermark"
+    )]
+    watermark_area: String,
 }
 
 impl TryFrom<FrameOptions> for FrameConfig {
     type Error = OliframeError;
 
     fn try_from(opts: FrameOptions) -> Result<Self, Self::Error> {
+        let antialias = Antialias::from_str(&opts.antialias)?;
+
         let aspect_ratio = opts
             .aspect_ratio
             .map(|ar| AspectRatio::from_str(&ar))
             .transpose()?;
+        let aspect_ratio_mode =
+            AspectRatioMode::from_str(&opts.aspect_ratio_mode).map_err(|_| {
+                OliframeError::InvalidInput(format!(
+                    "Invalid aspect ratio mode: {}",
+                    opts.aspect_ratio_mode
+                ))
+            })?;
+        let focus = opts
+            .focus
+            .map(|focus| FocusPoint::from_str(&focus))
+            .transpose()?;
+        if focus.is_some() && aspect_ratio_mode != AspectRatioMode::Crop {
+            return Err(OliframeError::InvalidInput(
+                "--focus only applies to --ar-mode crop.".to_string(),
+            ));
+        }
+
+        let background = match opts.background_image {
+            Some(path) => {
+                let fit = TextureFit::from_str(&opts.background_fit).map_err(|_| {
+                    OliframeError::InvalidInput(format!(
+                        "Invalid background fit: {}",
+                        opts.background_fit
+                    ))
+                })?;
+                Background::Texture(Texture::load(&path, fit)?)
+            }
+            None => Background::try_new(&opts.background, opts.blur_radius, opts.dim)?,
+        };
+
+        let blending = Blending::from_str(&opts.blending).map_err(|_| {
+            OliframeError::InvalidInput(format!("Invalid blending: {}", opts.blending))
+        })?;
+
+        let caption = opts
+            .caption
+            .map(|text| -> Result<Caption, OliframeError> {
+                let font = opts.caption_font.as_ref().ok_or_else(|| {
+                    OliframeError::InvalidInput("Captions require a font file.".to_string())
+                })?;
+                let side = Side::from_str(&opts.caption_margin).map_err(|_| {
+                    OliframeError::InvalidInput(format!(
+                        "Invalid caption margin: {}",
+                        opts.caption_margin
+                    ))
+                })?;
+                Ok(Caption::new(
+                    Template::from_str(&text)?,
+                    Caption::load_font(font)?,
+                    Length::from_str(&opts.caption_size)?,
+                    parse_color(&opts.caption_color, "caption")?,
+                    RelativePosition::from_str(&opts.caption_position)?,
+                    side,
+                )
+                .with_grow(opts.caption_grow))
+            })
+            .transpose()?;
 
-        let color = opts
-            .color
-            .parse::<Color>()
-            .map(|c| Rgba(c.to_rgba8()))
-            .map_err(|_| OliframeError::InvalidInput("Invalid color.".to_string()))?;
+        let color = Fill::from_str(&opts.color)?;
 
         let margins = opts
             .margins
@@ -57,70 +390,324 @@ impl TryFrom<FrameOptions> for FrameConfig {
             .transpose()?
             .unwrap_or_default();
 
+        let margin_basis = MarginBasis::from_str(&opts.margin_basis).map_err(|_| {
+            OliframeError::InvalidInput(format!("Invalid margin basis: {}", opts.margin_basis))
+        })?;
+        let size = opts.size.map(|s| Size::from_str(&s)).transpose()?;
+        let resampling = Resampling::from_str(&opts.resample).map_err(|_| {
+            OliframeError::InvalidInput(format!("Invalid resampling filter: {}", opts.resample))
+        })?;
+
+        let mask = match opts.mask_image {
+            Some(path) => Mask::load(&path)?,
+            None => Mask::from_str(&opts.mask)?,
+        };
+
+        let mats = opts
+            .mats
+            .iter()
+            .map(|m| Mat::from_str(m))
+            .collect::<Result<Vec<Mat>, OliframeError>>()?;
+        let relative_total = mats
+            .iter()
+            .map(|mat| mat.margins().relative_total())
+            .sum::<f32>()
+            + margins.relative_total();
+        if margin_basis == MarginBasis::Output && relative_total >= 2. {
+            return Err(OliframeError::InvalidInput(
+                "Margins and mats measured against the output must add up to less than 200%."
+                    .to_string(),
+            ));
+        }
+
         let position = opts
             .position
             .map(|p| RelativePosition::from_str(&p))
             .transpose()?
             .unwrap_or_default();
 
-        Ok(FrameConfig::new(
-            aspect_ratio,
-            color,
-            opts.corner_radius,
-            margins,
-            position,
-        ))
+        let corner_radius = opts
+            .corner_radius
+            .map(|r| CornerRadius::from_str(&r))
+            .transpose()?;
+
+        let canvas_radius = opts
+            .canvas_radius
+            .map(|r| CornerRadius::from_str(&r))
+            .transpose()?;
+
+        let corner_shape = CornerShape::from_str(&opts.corner_shape)?;
+
+        let matte = parse_color(&opts.matte, "matte")?;
+
+        let strip = opts.strip.as_deref().map(parse_strip).transpose()?;
+        let metadata =
+            KeepMetadata::from_str(&opts.metadata)?.with_strip(strip.unwrap_or_default());
+
+        let keyline = opts
+            .keyline
+            .map(|width| -> Result<Keyline, OliframeError> {
+                Ok(Keyline::new(
+                    Length::from_str(&width)?,
+                    Length::from_str(&opts.keyline_gap)?,
+                    parse_color(&opts.keyline_color, "keyline")?,
+                ))
+            })
+            .transpose()?;
+
+        let transparency = Transparency::from_str(&opts.transparency).map_err(|_| {
+            OliframeError::InvalidInput(format!("Invalid transparency: {}", opts.transparency))
+        })?;
+
+        let shadow_color = parse_color(&opts.shadow_color, "shadow")?;
+
+        let shadow = opts
+            .shadow
+            .map(|s| Shadow::from_str(&s)?.with_color(shadow_color, opts.shadow_opacity))
+            .transpose()?;
+
+        let watermark = opts
+            .watermark
+            .map(|path| -> Result<Watermark, OliframeError> {
+                let area = WatermarkArea::from_str(&opts.watermark_area).map_err(|_| {
+                    OliframeError::InvalidInput(format!(
+                        "Invalid watermark area: {}",
+                        opts.watermark_area
+                    ))
+                })?;
+                Watermark::load(
+                    &path,
+                    Length::from_str(&opts.watermark_size)?,
+                    Length::from_str(&opts.watermark_margin)?,
+                    RelativePosition::from_str(&opts.watermark_position)?,
+                    area,
+                )?
+                .with_opacity(opts.watermark_opacity)
+            })
+            .transpose()?;
+
+        Ok(
+            FrameConfig::new(aspect_ratio, color, corner_radius, margins, position)
+                .with_antialias(antialias)
+                .with_aspect_ratio_mode(aspect_ratio_mode, focus)
+                .with_background(background)
+                .with_blending(blending)
+                .with_canvas_radius(canvas_radius)
+                .with_caption(caption)
+                .with_corner_shape(corner_shape)
+                .with_keyline(keyline)
+                .with_margin_basis(margin_basis)
+                .with_mask(mask)
+                .with_mats(mats)
+                .with_matte(matte)
+                .with_metadata(metadata)
+                .with_resampling(resampling)
+                .with_shadow(shadow)
+                .with_size(size)
+                .with_transparency(transparency)
+                .with_watermark(watermark),
+        )
     }
 }
 
+/// Parse a CSS color for the named element of the frame.
+fn parse_color(input: &str, element: &str) -> Result<Rgba<u8>, OliframeError> {
+    input
+        .parse::<Color>()
+        .map(|c| Rgba(c.to_rgba8()))
+        .map_err(|_| OliframeError::InvalidInput(format!("Invalid {element} color.")))
+}
+
+/// Parse a comma-separated list of sensitive metadata groups, or the "privacy" preset.
+fn parse_strip(input: &str) -> Result<Vec<Sensitive>, OliframeError> {
+    let mut groups = Vec::new();
+    for name in input.split(',').map(str::trim) {
+        if name.eq_ignore_ascii_case("privacy") {
+            groups.extend(Sensitive::iter());
+        } else {
+            groups.push(Sensitive::from_str(name).map_err(|_| {
+                OliframeError::InvalidInput(format!("Invalid metadata group: {name}"))
+            })?);
+        }
+    }
+    Ok(groups)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn default_opts() -> FrameOptions {
+        FrameOptions {
+            antialias: "analytic".to_string(),
+            aspect_ratio: None,
+            aspect_ratio_mode: "pad".to_string(),
+            background: "color".to_string(),
+            background_image: None,
+            background_fit: "cover".to_string(),
+            blending: "srgb".to_string(),
+            blur_radius: 5.,
+            dim: 20.,
+            caption: None,
+            caption_font: None,
+            caption_size: "40%".to_string(),
+            caption_color: "black".to_string(),
+            caption_position: "center".to_string(),
+            caption_margin: "bottom".to_string(),
+            caption_grow: false,
+            color: "white".to_string(),
+            corner_radius: None,
+            canvas_radius: None,
+            corner_shape: "circle".to_string(),
+            focus: None,
+            keyline: None,
+            keyline_color: "black".to_string(),
+            keyline_gap: "0".to_string(),
+            margins: None,
+            margin_basis: "average".to_string(),
+            mask: "rectangle".to_string(),
+            mask_image: None,
+            mats: Vec::new(),
+            matte: "white".to_string(),
+            metadata: "all".to_string(),
+            position: None,
+            shadow: None,
+            shadow_color: "black".to_string(),
+            shadow_opacity: 50.,
+            size: None,
+            resample: "lanczos".to_string(),
+            strip: None,
+            transparency: "flatten".to_string(),
+            watermark: None,
+            watermark_size: "50%".to_string(),
+            watermark_opacity: 100.,
+            watermark_margin: "25%".to_string(),
+            watermark_position: "bottom right".to_string(),
+            watermark_area: "frame".to_string(),
+        }
+    }
+
     #[test]
     fn parse_frame_options() {
         let opts = FrameOptions {
             aspect_ratio: Some("16:9".to_string()),
             color: "black".to_string(),
-            corner_radius: Some(5),
+            corner_radius: Some("5".to_string()),
             margins: Some("10".to_string()),
             position: Some("center".to_string()),
+            ..default_opts()
         };
 
         let config = FrameConfig::try_from(opts).unwrap();
         assert_eq!(config.aspect_ratio().as_ref().unwrap().inner(), 16.0 / 9.0);
-        assert_eq!(config.color(), &Rgba([0, 0, 0, 255]));
-        assert_eq!(config.corner_radius(), &Some(5));
-        assert_eq!(config.margins().values(), &[0.1]);
+        assert_eq!(config.color(), &Fill::Solid(Rgba([0, 0, 0, 255])));
+        assert_eq!(
+            config.corner_radius(),
+            &Some(CornerRadius::from_str("5").unwrap())
+        );
+        assert_eq!(config.margins().values(), &[Length::Relative(0.1)]);
         assert_eq!(config.position(), &RelativePosition::default());
     }
 
     #[test]
-    fn parse_frame_options_with_defaults() {
+    fn parse_margin_basis() {
         let opts = FrameOptions {
-            aspect_ratio: None,
-            color: "white".to_string(),
-            corner_radius: None,
-            margins: None,
-            position: None,
+            margin_basis: "Short".to_string(),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.margin_basis(), &MarginBasis::Short);
+
+        let opts = FrameOptions {
+            margin_basis: "output".to_string(),
+            margins: Some("50".to_string()),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        // Mats count toward the total, along with the margins.
+        let opts = FrameOptions {
+            margin_basis: "output".to_string(),
+            margins: Some("20".to_string()),
+            mats: vec!["60:black".to_string()],
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        let opts = FrameOptions {
+            margin_basis: "perimeter".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_size() {
+        let opts = FrameOptions {
+            size: Some("1080x1350".to_string()),
+            resample: "Cubic".to_string(),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.size(), &Some(Size::from((1080, 1350))));
+        assert_eq!(config.resampling(), &Resampling::Cubic);
+
+        let opts = FrameOptions {
+            size: Some("1080x1350".to_string()),
+            resample: "bicubic".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_aspect_ratio_mode() {
+        let opts = FrameOptions {
+            aspect_ratio: Some("4:5".to_string()),
+            aspect_ratio_mode: "Crop".to_string(),
+            focus: Some("30,60".to_string()),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.aspect_ratio_mode(), &AspectRatioMode::Crop);
+        assert_eq!(
+            config.focus(),
+            &Some(FocusPoint::from_str("30,60").unwrap())
+        );
+
+        let opts = FrameOptions {
+            aspect_ratio: Some("4:5".to_string()),
+            aspect_ratio_mode: "stretch".to_string(),
+            ..default_opts()
         };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        let opts = FrameOptions {
+            aspect_ratio: Some("4:5".to_string()),
+            focus: Some("30,60".to_string()),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_frame_options_with_defaults() {
+        let opts = default_opts();
 
         let config = FrameConfig::try_from(opts).unwrap();
         assert!(config.aspect_ratio().is_none());
-        assert_eq!(config.color(), &Rgba([255, 255, 255, 255]));
+        assert_eq!(config.color(), &Fill::Solid(Rgba([255, 255, 255, 255])));
         assert!(config.corner_radius().is_none());
-        assert_eq!(config.margins().values(), &[0.05]);
+        assert_eq!(config.background(), &Background::Color);
+        assert_eq!(config.margins().values(), &[Length::Relative(0.05)]);
         assert_eq!(config.position(), &RelativePosition::default());
     }
 
     #[test]
     fn parsing_with_invalid_color_fails() {
         let opts = FrameOptions {
-            aspect_ratio: None,
             color: "invalid".to_string(),
-            corner_radius: None,
-            margins: None,
-            position: None,
+            ..default_opts()
         };
 
         let result = FrameConfig::try_from(opts);
@@ -130,4 +717,346 @@ mod tests {
             "Invalid input: Invalid color."
         );
     }
+
+    #[test]
+    fn parse_gradient_color() {
+        let opts = FrameOptions {
+            color: "linear-gradient(45deg, #fff, #ccc)".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert!(matches!(config.color(), Fill::Linear { .. }));
+    }
+
+    #[test]
+    fn parse_blur_background() {
+        let opts = FrameOptions {
+            background: "blur".to_string(),
+            blur_radius: 10.,
+            dim: 50.,
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(
+            config.background(),
+            &Background::Blur {
+                radius: 0.1,
+                dim: 0.5
+            }
+        );
+    }
+
+    #[test]
+    fn parse_texture_background() {
+        let opts = FrameOptions {
+            background_image: Some(PathBuf::from("images/test/test_03.png")),
+            background_fit: "tile".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert!(matches!(config.background(), Background::Texture(_)));
+    }
+
+    #[test]
+    fn parsing_with_invalid_background_fit_fails() {
+        let opts = FrameOptions {
+            background_image: Some(PathBuf::from("images/test/test_03.png")),
+            background_fit: "mosaic".to_string(),
+            ..default_opts()
+        };
+
+        let result = FrameConfig::try_from(opts);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid input: Invalid background fit: mosaic"
+        );
+    }
+
+    #[test]
+    fn parse_shadow() {
+        let opts = FrameOptions {
+            shadow: Some("-4 8 12".to_string()),
+            shadow_color: "red".to_string(),
+            shadow_opacity: 25.,
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        let shadow = config.shadow().as_ref().unwrap();
+        assert_eq!(shadow.offset_x(), -4);
+        assert_eq!(shadow.blur(), 12);
+        assert_eq!(shadow.color(), &Rgba([255, 0, 0, 255]));
+        assert_eq!(shadow.opacity(), 0.25);
+    }
+
+    #[test]
+    fn parse_caption() {
+        let opts = FrameOptions {
+            caption: Some("{model|\"Hello\"}".to_string()),
+            caption_font: Some(PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")),
+            caption_size: "24px".to_string(),
+            caption_color: "gray".to_string(),
+            caption_position: "left".to_string(),
+            caption_margin: "Top".to_string(),
+            caption_grow: true,
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        let caption = config.caption().as_ref().unwrap();
+        assert_eq!(
+            caption.text(),
+            &Template::from_str("{model|\"Hello\"}").unwrap()
+        );
+        assert_eq!(caption.size(), &Length::Pixels(24.));
+        assert_eq!(caption.color(), &Rgba([128, 128, 128, 255]));
+        assert_eq!(
+            caption.position(),
+            &RelativePosition::from_str("left").unwrap()
+        );
+        assert_eq!(caption.side(), &Side::Top);
+        assert!(caption.grow());
+
+        let opts = FrameOptions {
+            caption: Some("Hello".to_string()),
+            caption_font: Some(PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")),
+            caption_margin: "middle".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        let opts = FrameOptions {
+            caption: Some("Hello".to_string()),
+            caption_font: Some(PathBuf::from("Cargo.toml")),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        let opts = FrameOptions {
+            caption: Some("{shutter}".to_string()),
+            caption_font: Some(PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_watermark() {
+        let opts = FrameOptions {
+            watermark: Some(PathBuf::from("images/test/test_03.png")),
+            watermark_size: "10%".to_string(),
+            watermark_opacity: 40.,
+            watermark_margin: "8px".to_string(),
+            watermark_position: "top left".to_string(),
+            watermark_area: "Image".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        let watermark = config.watermark().as_ref().unwrap();
+        assert_eq!(watermark.size(), &Length::Relative(0.1));
+        assert_eq!(watermark.opacity(), 0.4);
+        assert_eq!(watermark.margin(), &Length::Pixels(8.));
+        assert_eq!(
+            watermark.position(),
+            &RelativePosition::from_str("top left").unwrap()
+        );
+        assert_eq!(watermark.area(), &WatermarkArea::Image);
+
+        let opts = FrameOptions {
+            watermark: Some(PathBuf::from("images/test/test_03.png")),
+            watermark_area: "canvas".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+
+        let opts = FrameOptions {
+            watermark: Some(PathBuf::from("Cargo.toml")),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_keyline() {
+        let opts = FrameOptions {
+            keyline: Some("0.2%".to_string()),
+            keyline_color: "gold".to_string(),
+            keyline_gap: "3px".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        let keyline = config.keyline().as_ref().unwrap();
+        assert_eq!(keyline.width(), &Length::Relative(0.002));
+        assert_eq!(keyline.gap(), &Length::Pixels(3.));
+        assert_eq!(keyline.color(), &Rgba([255, 215, 0, 255]));
+    }
+
+    #[test]
+    fn parse_mats() {
+        let opts = FrameOptions {
+            mats: vec!["2:black".to_string(), "0.5,3:gold".to_string()],
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.mats().len(), 2);
+        assert_eq!(
+            config.mats()[1].margins().values(),
+            &[Length::Relative(0.5), Length::Relative(0.03)]
+        );
+    }
+
+    #[test]
+    fn parse_canvas_radius_and_matte() {
+        let opts = FrameOptions {
+            canvas_radius: Some("4%".to_string()),
+            matte: "black".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(
+            config.canvas_radius(),
+            &Some(CornerRadius::from_str("4%").unwrap())
+        );
+        assert_eq!(config.matte(), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn parse_metadata() {
+        let config = FrameConfig::try_from(default_opts()).unwrap();
+        assert_eq!(config.metadata(), &KeepMetadata::default());
+
+        let opts = FrameOptions {
+            metadata: "ICC, exif".to_string(),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert!(config.metadata().exif() && config.metadata().icc());
+        assert!(!config.metadata().xmp());
+
+        let opts = FrameOptions {
+            metadata: "exif,gps".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_strip_groups() {
+        let opts = FrameOptions {
+            strip: Some("GPS, owner".to_string()),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(
+            config.metadata().strip(),
+            &vec![Sensitive::Gps, Sensitive::Owner]
+        );
+
+        let opts = FrameOptions {
+            strip: Some("privacy".to_string()),
+            ..default_opts()
+        };
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.metadata().strip().len(), 3);
+
+        let opts = FrameOptions {
+            strip: Some("gps,email".to_string()),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_antialias() {
+        let opts = FrameOptions {
+            antialias: "4x".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.antialias(), &Antialias::Supersample(4));
+    }
+
+    #[test]
+    fn parse_corner_shape() {
+        let opts = FrameOptions {
+            corner_shape: "squircle:4".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.corner_shape(), &CornerShape::Squircle(4.));
+
+        let opts = FrameOptions {
+            corner_shape: "oval".to_string(),
+            ..default_opts()
+        };
+        assert!(FrameConfig::try_from(opts).is_err());
+    }
+
+    #[test]
+    fn parse_mask() {
+        let opts = FrameOptions {
+            mask: "polygon:6".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.mask(), &Mask::Polygon(6));
+
+        let opts = FrameOptions {
+            mask_image: Some(PathBuf::from("images/test/test_03.png")),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert!(matches!(config.mask(), Mask::Alpha(_)));
+    }
+
+    #[test]
+    fn parse_transparency() {
+        let opts = FrameOptions {
+            transparency: "keep".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.transparency(), &Transparency::Keep);
+
+        let opts = FrameOptions {
+            transparency: "drop".to_string(),
+            ..default_opts()
+        };
+        assert_eq!(
+            FrameConfig::try_from(opts).unwrap_err().to_string(),
+            "Invalid input: Invalid transparency: drop"
+        );
+    }
+
+    #[test]
+    fn parse_blending() {
+        let opts = FrameOptions {
+            blending: "linear".to_string(),
+            ..default_opts()
+        };
+
+        let config = FrameConfig::try_from(opts).unwrap();
+        assert_eq!(config.blending(), &Blending::Linear);
+
+        let opts = FrameOptions {
+            blending: "gamma".to_string(),
+            ..default_opts()
+        };
+        assert_eq!(
+            FrameConfig::try_from(opts).unwrap_err().to_string(),
+            "Invalid input: Invalid blending: gamma"
+        );
+    }
 }
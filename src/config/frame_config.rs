@@ -1,6 +1,13 @@
 //! Frame configuration.
 
-use crate::geometry::{AspectRatio, Margins, RelativePosition};
+use crate::frame::{
+    Antialias, Background, Blending, Caption, Fill, KeepMetadata, Keyline, Mask, Mat, Resampling,
+    Shadow, Transparency, Watermark,
+};
+use crate::geometry::{
+    AspectRatio, AspectRatioMode, CornerRadius, CornerShape, FocusPoint, MarginBasis, Margins,
+    RelativePosition, Size,
+};
 use derive_getters::Getters;
 use image::Rgba;
 use std::fmt::Debug;
@@ -8,45 +15,216 @@ use std::fmt::Debug;
 /// Configuration parameters for the frame.
 #[derive(Debug, Getters)]
 pub struct FrameConfig {
+    /// How the coverage of pixels along rounded edges is measured.
+    antialias: Antialias,
     /// The desired aspect ratio of the final output image.
     aspect_ratio: Option<AspectRatio>,
-    /// The color of the frame around the image
-    color: Rgba<u8>,
-    /// The radius of the frame's corners.
-    corner_radius: Option<u32>,
-    /// The relative margins around the image.
+    /// How the output image is brought to its aspect ratio.
+    aspect_ratio_mode: AspectRatioMode,
+    /// The source used to fill the frame around the image.
+    background: Background,
+    /// The color space in which colors are blended.
+    blending: Blending,
+    /// The radius of the corners of the whole output image.
+    canvas_radius: Option<CornerRadius>,
+    /// The line of text rendered in the frame margin.
+    caption: Option<Caption>,
+    /// The color or gradient of the frame around the image
+    color: Fill,
+    /// The radius of the image's corners.
+    corner_radius: Option<CornerRadius>,
+    /// The curve used to cut the rounded corners of the image and the canvas.
+    corner_shape: CornerShape,
+    /// The part of the image kept in view when it is cropped to the aspect ratio.
+    focus: Option<FocusPoint>,
+    /// The thin stroke drawn around the image, inside the frame.
+    keyline: Option<Keyline>,
+    /// The dimension that relative margins are a fraction of.
+    margin_basis: MarginBasis,
+    /// The margins around the image.
     margins: Margins,
+    /// The shape of the window through which the image is seen.
+    mask: Mask,
+    /// The color used in place of transparency for formats without an alpha channel.
+    matte: Rgba<u8>,
+    /// The mats nested between the image and the outer frame, listed from the image outward.
+    mats: Vec<Mat>,
+    /// The kinds of metadata carried over from each input image to its output.
+    metadata: KeepMetadata,
     /// The relative position of the image within the frame.
     position: RelativePosition,
+    /// The filter used to resize the image to fit the exact output size.
+    resampling: Resampling,
+    /// The drop shadow cast by the image onto the frame.
+    shadow: Option<Shadow>,
+    /// The exact size of the output image, which the image is resized to fit along with its frame.
+    size: Option<Size>,
+    /// What happens to transparent and semi-transparent pixels of the input image.
+    transparency: Transparency,
+    /// The logo placed over the frame or the image.
+    watermark: Option<Watermark>,
 }
 
 impl FrameConfig {
     /// Create a new frame configuration.
     pub fn new(
         aspect_ratio: Option<AspectRatio>,
-        color: Rgba<u8>,
-        corner_radius: Option<u32>,
+        color: Fill,
+        corner_radius: Option<CornerRadius>,
         margins: Margins,
         position: RelativePosition,
     ) -> Self {
         Self {
+            antialias: Antialias::default(),
             aspect_ratio,
+            aspect_ratio_mode: AspectRatioMode::default(),
+            background: Background::default(),
+            blending: Blending::default(),
+            canvas_radius: None,
+            caption: None,
             color,
             corner_radius,
+            corner_shape: CornerShape::default(),
+            focus: None,
+            keyline: None,
+            margin_basis: MarginBasis::default(),
             margins,
+            mask: Mask::default(),
+            matte: Rgba([255, 255, 255, 255]),
+            mats: Vec::new(),
+            metadata: KeepMetadata::default(),
             position,
+            resampling: Resampling::default(),
+            shadow: None,
+            size: None,
+            transparency: Transparency::default(),
+            watermark: None,
         }
     }
+
+    /// Set how the coverage of pixels along rounded edges is measured.
+    pub fn with_antialias(mut self, antialias: Antialias) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    /// Set how the output image is brought to its aspect ratio,
+    /// and the part of the image kept in view when it is cropped.
+    pub fn with_aspect_ratio_mode(
+        mut self,
+        aspect_ratio_mode: AspectRatioMode,
+        focus: Option<FocusPoint>,
+    ) -> Self {
+        self.aspect_ratio_mode = aspect_ratio_mode;
+        self.focus = focus;
+        self
+    }
+
+    /// Set the source used to fill the frame around the image.
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Set the color space in which colors are blended.
+    pub fn with_blending(mut self, blending: Blending) -> Self {
+        self.blending = blending;
+        self
+    }
+
+    /// Set the radius of the corners of the whole output image.
+    pub fn with_canvas_radius(mut self, canvas_radius: Option<CornerRadius>) -> Self {
+        self.canvas_radius = canvas_radius;
+        self
+    }
+
+    /// Set the line of text rendered in the frame margin.
+    pub fn with_caption(mut self, caption: Option<Caption>) -> Self {
+        self.caption = caption;
+        self
+    }
+
+    /// Set the curve used to cut the rounded corners of the image and the canvas.
+    pub fn with_corner_shape(mut self, corner_shape: CornerShape) -> Self {
+        self.corner_shape = corner_shape;
+        self
+    }
+
+    /// Set the shape of the window through which the image is seen.
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Set the color used in place of transparency for formats without an alpha channel.
+    pub fn with_matte(mut self, matte: Rgba<u8>) -> Self {
+        self.matte = matte;
+        self
+    }
+
+    /// Set the drop shadow cast by the image onto the frame.
+    pub fn with_shadow(mut self, shadow: Option<Shadow>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    /// Set the exact size of the output image.
+    pub fn with_size(mut self, size: Option<Size>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the filter used to resize the image to fit the exact output size.
+    pub fn with_resampling(mut self, resampling: Resampling) -> Self {
+        self.resampling = resampling;
+        self
+    }
+
+    /// Set the thin stroke drawn around the image, inside the frame.
+    pub fn with_keyline(mut self, keyline: Option<Keyline>) -> Self {
+        self.keyline = keyline;
+        self
+    }
+
+    /// Set the dimension that relative margins are a fraction of.
+    pub fn with_margin_basis(mut self, margin_basis: MarginBasis) -> Self {
+        self.margin_basis = margin_basis;
+        self
+    }
+
+    /// Set the mats nested between the image and the outer frame, listed from the image outward.
+    pub fn with_mats(mut self, mats: Vec<Mat>) -> Self {
+        self.mats = mats;
+        self
+    }
+
+    /// Set the kinds of metadata carried over from each input image to its output.
+    pub fn with_metadata(mut self, metadata: KeepMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set what happens to transparent and semi-transparent pixels of the input image.
+    pub fn with_transparency(mut self, transparency: Transparency) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
+    /// Set the logo placed over the frame or the image.
+    pub fn with_watermark(mut self, watermark: Option<Watermark>) -> Self {
+        self.watermark = watermark;
+        self
+    }
 }
 
 impl Default for FrameConfig {
     fn default() -> Self {
-        Self {
-            aspect_ratio: None,
-            color: Rgba([255, 255, 255, 255]),
-            corner_radius: None,
-            margins: Margins::default(),
-            position: RelativePosition::default(),
-        }
+        Self::new(
+            None,
+            Fill::default(),
+            None,
+            Margins::default(),
+            RelativePosition::default(),
+        )
     }
 }
@@ -0,0 +1,244 @@
+//! The antialias module is responsible for measuring how much of a pixel a rounded corner covers.
+use crate::errors::OliframeError;
+use crate::geometry::CornerShape;
+use std::str::FromStr;
+
+/// The largest number of samples per side allowed when supersampling.
+const MAX_SAMPLES: u32 = 16;
+
+/// The number of slices used to integrate the coverage of corners without a closed-form area.
+const SLICES: u32 = 64;
+
+/// How the coverage of pixels along rounded edges is measured.
+///
+/// The user can provide the quality in the following formats:
+/// - `analytic`: the exact area of the pixel inside the curve
+/// - `4x`: the share of a 4×4 grid of samples inside the curve (up to 16×16)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Antialias {
+    /// The exact area of the pixel inside the curve.
+    #[default]
+    Analytic,
+    /// The share of an N×N grid of samples inside the curve.
+    Supersample(u32),
+}
+
+impl Antialias {
+    /// The fraction (0 to 1) of the pixel square `[u, u + 1] × [v, v + 1]` inside a corner of the
+    /// given shape and radius centered on the origin, where `u` and `v` are the distances from the
+    /// corner's center to the nearest edges of the pixel.
+    ///
+    /// Circles use their exact area; other shapes integrate their height across the pixel.
+    pub fn corner_coverage(&self, shape: CornerShape, u: f32, v: f32, radius: f32) -> f32 {
+        if shape != CornerShape::Circle {
+            return match self {
+                Antialias::Analytic => {
+                    let slice = 1. / SLICES as f32;
+                    (0..SLICES)
+                        .map(|i| {
+                            let height = shape.height(u + (i as f32 + 0.5) * slice, radius);
+                            (height - v).clamp(0., 1.) * slice
+                        })
+                        .sum::<f32>()
+                        .clamp(0., 1.)
+                }
+                Antialias::Supersample(samples) => {
+                    let n = *samples as f32;
+                    let offsets = (0..*samples).map(|i| (i as f32 + 0.5) / n);
+                    let inside = offsets
+                        .clone()
+                        .flat_map(|du| offsets.clone().map(move |dv| (du, dv)))
+                        .filter(|(du, dv)| shape.contains(u + du, v + dv, radius))
+                        .count();
+                    inside as f32 / (n * n)
+                }
+            };
+        }
+
+        let (u, v, radius) = (u as f64, v as f64, radius as f64);
+        let coverage = match self {
+            Antialias::Analytic => {
+                area_beyond(u, v, radius)
+                    - area_beyond(u + 1., v, radius)
+                    - area_beyond(u, v + 1., radius)
+                    + area_beyond(u + 1., v + 1., radius)
+            }
+            Antialias::Supersample(samples) => {
+                let n = *samples as f64;
+                let offsets = (0..*samples).map(|i| (i as f64 + 0.5) / n);
+                let inside = offsets
+                    .clone()
+                    .flat_map(|du| offsets.clone().map(move |dv| (du, dv)))
+                    .filter(|(du, dv)| (u + du).powi(2) + (v + dv).powi(2) <= radius * radius)
+                    .count();
+                inside as f64 / (n * n)
+            }
+        };
+        coverage.clamp(0., 1.) as f32
+    }
+}
+
+impl FromStr for Antialias {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim().to_lowercase();
+        if input == "analytic" {
+            return Ok(Antialias::Analytic);
+        }
+
+        input
+            .strip_suffix('x')
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|n| (1..=MAX_SAMPLES).contains(n))
+            .map(Antialias::Supersample)
+            .ok_or_else(|| {
+                OliframeError::InvalidInput(format!(
+                    "Anti-aliasing must be \"analytic\" or a sample grid from \"1x\" to \"{MAX_SAMPLES}x\" (received {input})."
+                ))
+            })
+    }
+}
+
+/// The area of the quarter circle of the given radius where both coordinates are beyond `a` and `b`.
+fn area_beyond(a: f64, b: f64, radius: f64) -> f64 {
+    if a * a + b * b >= radius * radius {
+        return 0.;
+    }
+    let end = (radius * radius - b * b).sqrt();
+    integral(end, radius) - integral(a, radius) - b * (end - a)
+}
+
+/// The antiderivative of `sqrt(radius² - u²)`.
+fn integral(u: f64, radius: f64) -> f64 {
+    let ratio = (u / radius).min(1.);
+    (u * (radius * radius - u * u).max(0.).sqrt() + radius * radius * ratio.asin()) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_4;
+
+    /// The area of a quarter circle of the given radius.
+    fn quarter_circle(radius: f64) -> f64 {
+        FRAC_PI_4 * radius * radius
+    }
+
+    #[test]
+    fn parse_antialias() {
+        assert_eq!(
+            "analytic".parse::<Antialias>().unwrap(),
+            Antialias::Analytic
+        );
+        assert_eq!(
+            "4x".parse::<Antialias>().unwrap(),
+            Antialias::Supersample(4)
+        );
+        assert!("0x".parse::<Antialias>().is_err());
+        assert!("32x".parse::<Antialias>().is_err());
+        assert!("smooth".parse::<Antialias>().is_err());
+    }
+
+    #[test]
+    fn single_pixel_radius_covers_a_quarter_circle() {
+        let coverage = Antialias::Analytic.corner_coverage(CornerShape::Circle, 0., 0., 1.);
+        assert!((coverage - FRAC_PI_4 as f32).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pixels_inside_and_outside_the_curve() {
+        for radius in [2., 10., 100.] {
+            assert_eq!(
+                Antialias::Analytic.corner_coverage(CornerShape::Circle, 0., 0., radius),
+                1.
+            );
+            assert_eq!(
+                Antialias::Analytic.corner_coverage(CornerShape::Circle, radius, 0., radius),
+                0.
+            );
+            assert_eq!(
+                Antialias::Analytic.corner_coverage(
+                    CornerShape::Circle,
+                    radius * 0.8,
+                    radius * 0.8,
+                    radius
+                ),
+                0.
+            );
+        }
+    }
+
+    #[test]
+    fn pixel_centered_on_the_curve_is_half_covered() {
+        for radius in [10., 50., 500.] {
+            // A pixel centered on the curve along the diagonal.
+            let edge = radius / std::f32::consts::SQRT_2 - 0.5;
+            let coverage =
+                Antialias::Analytic.corner_coverage(CornerShape::Circle, edge, edge, radius);
+            assert!((coverage - 0.5).abs() < 0.05, "radius {radius}: {coverage}");
+        }
+    }
+
+    #[test]
+    fn corner_coverage_adds_up_to_the_circle_area() {
+        for radius in [1, 3, 8, 25] {
+            for antialias in [Antialias::Analytic, Antialias::Supersample(8)] {
+                let total: f64 = (0..radius)
+                    .flat_map(|u| (0..radius).map(move |v| (u, v)))
+                    .map(|(u, v)| {
+                        antialias.corner_coverage(
+                            CornerShape::Circle,
+                            u as f32,
+                            v as f32,
+                            radius as f32,
+                        ) as f64
+                    })
+                    .sum();
+                let expected = quarter_circle(radius as f64);
+                let tolerance = match antialias {
+                    Antialias::Analytic => 1e-3,
+                    Antialias::Supersample(_) => 0.05 * expected,
+                };
+                assert!(
+                    (total - expected).abs() < tolerance,
+                    "{antialias:?} at radius {radius}: {total} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn supersampling_approximates_analytic_coverage() {
+        let analytic = Antialias::Analytic.corner_coverage(CornerShape::Circle, 7., 6., 10.);
+        let supersampled =
+            Antialias::Supersample(16).corner_coverage(CornerShape::Circle, 7., 6., 10.);
+        assert!((analytic - supersampled).abs() < 0.02);
+        assert!(analytic > 0.5 && analytic < 0.7);
+    }
+
+    #[test]
+    fn shaped_corner_coverage_adds_up_to_the_shape_area() {
+        let radius = 20;
+        let area = |shape: CornerShape, antialias: Antialias| -> f32 {
+            (0..radius)
+                .flat_map(|u| (0..radius).map(move |v| (u, v)))
+                .map(|(u, v)| antialias.corner_coverage(shape, u as f32, v as f32, radius as f32))
+                .sum()
+        };
+
+        // A chamfer cuts the corner square in half.
+        let chamfer = area(CornerShape::Chamfer, Antialias::Analytic);
+        assert!((chamfer - 200.).abs() < 0.1, "{chamfer}");
+        let chamfer = area(CornerShape::Chamfer, Antialias::Supersample(8));
+        assert!((chamfer - 200.).abs() < 2., "{chamfer}");
+
+        // A squircle fills more of the corner than a circle, but never all of it.
+        let circle = area(CornerShape::Circle, Antialias::Analytic);
+        let squircle = area(CornerShape::Squircle(5.), Antialias::Analytic);
+        assert!(squircle > circle && squircle < 400.);
+        let supersampled = area(CornerShape::Squircle(5.), Antialias::Supersample(8));
+        assert!((squircle - supersampled).abs() < 2.);
+    }
+}
@@ -0,0 +1,202 @@
+//! The background module is responsible for filling the frame area around the image.
+use crate::errors::OliframeError;
+use crate::frame::color_space::ColorSpace;
+use crate::frame::{Blending, Fill, Texture};
+use crate::geometry::{Size, percentage};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, RgbaImage};
+
+/// The source used to fill the frame area around the image.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Background {
+    /// The frame is filled with the frame fill (a color or gradient).
+    #[default]
+    Color,
+    /// The frame is filled with a scaled-up, blurred copy of the input image.
+    Blur {
+        /// The blur strength, as a fraction of (width + height) / 2 of the output image.
+        radius: f32,
+        /// The fraction (0 to 1) by which the blurred image is tinted toward the frame fill.
+        dim: f32,
+    },
+    /// The frame is filled with an image file, such as a paper or canvas texture.
+    Texture(Texture),
+}
+
+impl Background {
+    /// Create a new background from the given mode name and blur parameters,
+    /// with the blur radius and dim amount given as percentages.
+    pub fn try_new(mode: &str, blur_radius: f32, dim: f32) -> Result<Self, OliframeError> {
+        match mode.trim().to_lowercase().as_str() {
+            "color" => Ok(Background::Color),
+            "blur" => Ok(Background::Blur {
+                radius: percentage(blur_radius, "Blur radius")?,
+                dim: percentage(dim, "Dim amount")?,
+            }),
+            _ => Err(OliframeError::InvalidInput(format!(
+                "Invalid background mode: {mode}"
+            ))),
+        }
+    }
+
+    /// Render the background for an image framed at the given output size.
+    ///
+    /// The background is rendered in sRGB, so the blurred copy of an image
+    /// in another color space is converted to sRGB before it is tinted.
+    pub fn render(
+        &self,
+        img: &DynamicImage,
+        color_space: Option<&ColorSpace>,
+        output_size: &Size,
+        fill: &Fill,
+        blending: Blending,
+    ) -> RgbaImage {
+        let (width, height) = output_size.dimensions();
+        match self {
+            Background::Color => RgbaImage::from_fn(width, height, |x, y| {
+                fill.sample(x, y, output_size, blending)
+            }),
+            Background::Blur { radius, dim } => {
+                let mut backdrop = blurred(img, output_size, *radius);
+                if let Some(color_space) = color_space {
+                    color_space.image_to_srgb(&mut backdrop);
+                }
+                backdrop.enumerate_pixels_mut().for_each(|(x, y, pixel)| {
+                    let tint = fill.sample(x, y, output_size, blending);
+                    *pixel = blending.interpolate(pixel, &tint, *dim);
+                });
+                backdrop
+            }
+            Background::Texture(texture) => {
+                let mut backdrop = RgbaImage::from_fn(width, height, |x, y| {
+                    fill.sample(x, y, output_size, blending)
+                });
+                texture.paint(&mut backdrop);
+                backdrop
+            }
+        }
+    }
+}
+
+/// Scale the image to cover the output size and blur it.
+///
+/// The blur is applied to a downscaled copy of the image,
+/// which is much faster than blurring at full size and indistinguishable once scaled back up.
+fn blurred(img: &DynamicImage, output_size: &Size, radius: f32) -> RgbaImage {
+    let (width, height) = output_size.dimensions();
+    let sigma = radius * (width + height) as f32 / 2.;
+    let scale = (sigma / 4.).max(1.);
+    let small_width = ((width as f32 / scale).round() as u32).max(1);
+    let small_height = ((height as f32 / scale).round() as u32).max(1);
+
+    let small = img
+        .resize_to_fill(small_width, small_height, FilterType::Triangle)
+        .to_rgba8();
+    let small = imageops::fast_blur(&small, sigma / scale);
+    imageops::resize(&small, width, height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::composite;
+    use image::Rgba;
+
+    #[test]
+    fn parse_background_modes() {
+        assert_eq!(
+            Background::try_new("color", 5., 20.).unwrap(),
+            Background::Color
+        );
+        assert_eq!(
+            Background::try_new("Blur", 5., 20.).unwrap(),
+            Background::Blur {
+                radius: 0.05,
+                dim: 0.2
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_background_mode_is_err() {
+        let result = Background::try_new("plaid", 5., 20.);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid input: Invalid background mode: plaid"
+        );
+    }
+
+    #[test]
+    fn invalid_blur_parameters_are_err() {
+        assert!(Background::try_new("blur", -1., 20.).is_err());
+        assert!(Background::try_new("blur", 5., -1.).is_err());
+        assert!(Background::try_new("blur", 5., 120.).is_err());
+        assert!(Background::try_new("blur", 120., 20.).is_err());
+    }
+
+    #[test]
+    fn small_blur_parameters_are_percentages() {
+        assert_eq!(
+            Background::try_new("blur", 0.5, 1.).unwrap(),
+            Background::Blur {
+                radius: 0.005,
+                dim: 0.01
+            }
+        );
+    }
+
+    #[test]
+    fn render_color_background() {
+        let img = DynamicImage::new_rgba8(10, 10);
+        let backdrop = Background::Color.render(
+            &img,
+            None,
+            &Size::new(20, 30),
+            &Fill::Solid(Rgba([1, 2, 3, 255])),
+            Blending::Srgb,
+        );
+        assert_eq!(backdrop.dimensions(), (20, 30));
+        assert!(backdrop.pixels().all(|p| *p == Rgba([1, 2, 3, 255])));
+    }
+
+    #[test]
+    fn render_blurred_background() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 20, Rgba([200, 100, 0, 255])));
+        let background = Background::Blur {
+            radius: 0.05,
+            dim: 0.5,
+        };
+        let backdrop = background.render(
+            &img,
+            None,
+            &Size::new(40, 30),
+            &Fill::Solid(Rgba([0, 0, 0, 255])),
+            Blending::Srgb,
+        );
+        assert_eq!(backdrop.dimensions(), (40, 30));
+        assert!(backdrop.pixels().all(|p| *p == Rgba([100, 50, 0, 255])));
+    }
+
+    #[test]
+    fn dim_blurred_background_in_linear_light() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 20, Rgba([200, 100, 0, 255])));
+        let background = Background::Blur {
+            radius: 0.05,
+            dim: 0.5,
+        };
+        let backdrop = background.render(
+            &img,
+            None,
+            &Size::new(40, 30),
+            &Fill::Solid(Rgba([0, 0, 0, 255])),
+            Blending::Linear,
+        );
+        // Half of the light is left: 0.289 and 0.064 in linear light, 146 and 71 in sRGB.
+        let dimmed = |channel: u8| {
+            let light = composite::srgb_to_linear(channel as f32 / 255.) / 2.;
+            (composite::linear_to_srgb(light) * 255.).round() as u8
+        };
+        assert_eq!((dimmed(200), dimmed(100)), (146, 71));
+        assert!(backdrop.pixels().all(|p| *p == Rgba([146, 71, 0, 255])));
+    }
+}
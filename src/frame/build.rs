@@ -1,31 +1,209 @@
 //! Module containing helper functions for image processing.
 use crate::config::FrameConfig;
 use crate::errors::OliframeError;
+use crate::frame::color_space;
+use crate::frame::mat;
+use crate::frame::metadata::Metadata;
 use crate::geometry::*;
-use image::{DynamicImage, ImageFormat, ImageReader};
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
 use std::path::PathBuf;
 
-/// Load an image from the given file path.
-pub fn load(image_file: &PathBuf) -> Result<(DynamicImage, ImageFormat), OliframeError> {
+/// Load an image, and the metadata stored alongside its pixels, from the given file path.
+///
+/// The image is turned upright according to its EXIF orientation, so every size is
+/// computed on the image as it is meant to be seen.
+pub fn load(image_file: &PathBuf) -> Result<(DynamicImage, ImageFormat, Metadata), OliframeError> {
     let img = ImageReader::open(image_file)
         .map_err(|_| OliframeError::ImageUnreadable(image_file.to_path_buf()))?;
     let fmt = img
         .format()
         .ok_or_else(|| OliframeError::ImageUnreadable(image_file.to_path_buf()))?;
-    let img = img
-        .decode()
+    let mut decoder = img
+        .into_decoder()
         .map_err(|_| OliframeError::ImageUnreadable(image_file.to_path_buf()))?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let icc = decoder.icc_profile().ok().flatten();
+    let mut img = DynamicImage::from_decoder(decoder)
+        .map_err(|_| OliframeError::ImageUnreadable(image_file.to_path_buf()))?;
+    // Outputs always have RGB pixels, which other profiles cannot describe.
+    let icc = match icc {
+        Some(icc) if !color_space::is_rgb(&icc) => {
+            log::debug!("Converting {image_file:?} to sRGB and dropping its non-RGB ICC profile");
+            img = color_space::to_srgb(&icc, img);
+            None
+        }
+        icc => icc,
+    };
+    if orientation != Orientation::NoTransforms {
+        log::debug!("Applying {orientation:?} orientation to {image_file:?}");
+        img.apply_orientation(orientation);
+    }
 
-    Ok((img, fmt))
+    Ok((img, fmt, Metadata::read(image_file).with_icc_profile(icc)))
+}
+
+/// Calculate the dimension that relative margins and mats around an image of the given size
+/// are a fraction of.
+///
+/// Margins measured against the output are resolved once for the whole stack of mats and margins,
+/// or against the requested output size when there is one.
+pub fn margin_dimension(input_size: &Size, config: &FrameConfig) -> f32 {
+    match (config.margin_basis(), config.size()) {
+        (MarginBasis::Output, Some(size)) => MarginBasis::Output.dimension(size),
+        (MarginBasis::Output, None) => MarginBasis::output_dimension(
+            input_size,
+            config
+                .mats()
+                .iter()
+                .map(|mat| mat.margins())
+                .chain([config.margins()]),
+        ),
+        (basis, _) => basis.dimension(input_size),
+    }
+}
+
+/// Calculate the border taken up by the mats around the image,
+/// grown as needed so the shadow and keyline are not clipped.
+///
+/// The caption is laid out in the margin outside this border.
+pub fn inner_border(input_size: &Size, config: &FrameConfig) -> Border {
+    let mut border = mat::total_border(config.mats(), margin_dimension(input_size, config));
+    if let Some(shadow) = config.shadow() {
+        border = border.at_least(&shadow.extent());
+    }
+    if let Some(keyline) = config.keyline() {
+        border = border.at_least(&keyline.extent(input_size));
+    }
+    border
+}
+
+/// Calculate the border around the image, including any mats,
+/// grown as needed so the shadow and keyline are not clipped, and grown to fit the caption.
+pub fn border(input_size: &Size, config: &FrameConfig) -> Border {
+    let dim = margin_dimension(input_size, config);
+    let mut border = (mat::total_border(config.mats(), dim) + config.margins().to_border(dim))
+        .at_least(&inner_border(input_size, config));
+    if let Some(caption) = config.caption() {
+        border = border + caption.extent();
+    }
+    border
+}
+
+/// Calculate the largest size the image can be resized to, keeping its aspect ratio,
+/// so that it fits in the given output size along with its border.
+///
+/// Returns `None` if the border alone does not leave room for the image.
+pub fn fit_to_size(input_size: &Size, output_size: &Size, config: &FrameConfig) -> Option<Size> {
+    let (width, height) = (input_size.width() as f32, input_size.height() as f32);
+    let scaled = |scale: f32| {
+        Size::from((
+            ((width * scale).round() as u32).max(1),
+            ((height * scale).round() as u32).max(1),
+        ))
+    };
+    let fits = |size: &Size| {
+        let framed = size_with_border(size, &border(size, config));
+        framed.width() <= output_size.width() && framed.height() <= output_size.height()
+    };
+
+    // The border grows with the image, so the largest scale that fits is found by bisection,
+    // starting from the scale at which the image alone fills the output.
+    let mut high = (output_size.width() as f32 / width).min(output_size.height() as f32 / height);
+    if fits(&scaled(high)) {
+        return Some(scaled(high));
+    }
+    let mut low = 0.;
+    for _ in 0..32 {
+        let middle = (low + high) / 2.;
+        if fits(&scaled(middle)) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(scaled(low)).filter(fits)
+}
+
+/// Calculate the largest crop of the image that, along with its border, has the given aspect ratio.
+///
+/// The border depends on the size of the crop, so the crop is refined a few times;
+/// any rounding left over is padded out when the output dimensions are calculated.
+pub fn crop_to_ratio(input_size: &Size, aspect_ratio: &AspectRatio, config: &FrameConfig) -> Size {
+    let ratio = aspect_ratio.for_size(input_size);
+    let (width, height) = (input_size.width() as f32, input_size.height() as f32);
+    let mut crop = input_size.clone();
+    for _ in 0..4 {
+        let border = border(&crop, config);
+        let border_width = (border.left() + border.right()) as f32;
+        let border_height = (border.top() + border.bottom()) as f32;
+        let crop_width = ratio * (height + border_height) - border_width;
+        crop = if crop_width <= width {
+            Size::from(((crop_width.round() as u32).max(1), input_size.height()))
+        } else {
+            let crop_height = (width + border_width) / ratio - border_height;
+            Size::from((input_size.width(), (crop_height.round() as u32).max(1)))
+        };
+    }
+    crop
+}
+
+/// Calculate the position of the crop within the image,
+/// centered on the focus point if there is one, or else anchored at the relative position.
+pub fn crop_position(
+    input_size: &Size,
+    crop: &Size,
+    relative_position: &RelativePosition,
+    focus: Option<&FocusPoint>,
+) -> Point {
+    let (spare_width, spare_height) = (
+        input_size.width() - crop.width(),
+        input_size.height() - crop.height(),
+    );
+    if let Some(focus) = focus {
+        return Point::new(
+            FocusPoint::offset(focus.x(), crop.width(), input_size.width()),
+            FocusPoint::offset(focus.y(), crop.height(), input_size.height()),
+        );
+    }
+
+    let x = match relative_position.horizontal() {
+        HorizontalPosition::Left => 0,
+        HorizontalPosition::Center => spare_width / 2,
+        HorizontalPosition::Right => spare_width,
+    };
+    let y = match relative_position.vertical() {
+        VerticalPosition::Top => 0,
+        VerticalPosition::Center => spare_height / 2,
+        VerticalPosition::Bottom => spare_height,
+    };
+    Point::new(x, y)
 }
 
 /// Calculate the dimensions of the output image.
-pub fn output_dimensions(input_size: &Size, border: &Border, config: &FrameConfig) -> Size {
-    if let Some(aspect_ratio) = config.aspect_ratio() {
+///
+/// Returns an error if the output would be larger than an image can be.
+pub fn output_dimensions(
+    input_size: &Size,
+    border: &Border,
+    config: &FrameConfig,
+) -> Result<Size, OliframeError> {
+    let size = if let Some(size) = config.size() {
+        size.clone()
+    } else if let Some(aspect_ratio) = config.aspect_ratio() {
         size_with_ratio(input_size, aspect_ratio, border)
     } else {
         size_with_border(input_size, border)
+    };
+    if size.width() > Size::MAX_SIDE || size.height() > Size::MAX_SIDE {
+        return Err(OliframeError::InvalidInput(format!(
+            "The framed image would be {}x{}, larger than the maximum of {max}x{max}.",
+            size.width(),
+            size.height(),
+            max = Size::MAX_SIDE
+        )));
     }
+    Ok(size)
 }
 
 /// Calculate the position of the image within the output frame.
@@ -52,27 +230,34 @@ pub fn position(
 
 /// Calculate the size of the output image with a border.
 pub fn size_with_border(img_size: &Size, border: &Border) -> Size {
-    let new_width = img_size.width() + border.left() + border.right();
-    let new_height = img_size.height() + border.top() + border.bottom();
+    let new_width = img_size
+        .width()
+        .saturating_add(border.left())
+        .saturating_add(border.right());
+    let new_height = img_size
+        .height()
+        .saturating_add(border.top())
+        .saturating_add(border.bottom());
     Size::from((new_width, new_height))
 }
 
 /// Calculate the size of the output image with a given aspect ratio.
 pub fn size_with_ratio(img_size: &Size, aspect_ratio: &AspectRatio, border: &Border) -> Size {
     let (img_width, img_height) = img_size.dimensions();
-    let frame_width = (img_width + border.left() + border.right()) as f32;
-    let frame_height = (img_height + border.top() + border.bottom()) as f32;
+    let frame_width = (img_width as f32) + (border.left() as f32) + (border.right() as f32);
+    let frame_height = (img_height as f32) + (border.top() as f32) + (border.bottom() as f32);
     let frame_ar = frame_width / frame_height;
+    let aspect_ratio = aspect_ratio.for_size(img_size);
 
-    if frame_ar < aspect_ratio.inner() {
+    if frame_ar < aspect_ratio {
         Size::from((
-            (frame_height * aspect_ratio.inner()).round() as u32,
+            (frame_height * aspect_ratio).round() as u32,
             frame_height as u32,
         ))
     } else {
         Size::from((
             frame_width as u32,
-            (frame_width / aspect_ratio.inner()).round() as u32,
+            (frame_width / aspect_ratio).round() as u32,
         ))
     }
 }
@@ -80,17 +265,43 @@ pub fn size_with_ratio(img_size: &Size, aspect_ratio: &AspectRatio, border: &Bor
 #[cfg(test)]
 mod tests {
     use super::*;
-    use image::Rgba;
+    use crate::frame::{Caption, Fill, Keyline, Mat, Shadow, Template};
+    use crate::test_utils::{TEST_FS_PREFIX, save_jpeg_with_orientation};
+    use image::{GenericImageView, Rgb, RgbImage, Rgba};
     use std::str::FromStr;
-    use tempfile::Builder;
+    use tempfile::{Builder, TempDir};
 
     #[test]
     fn load_known_image() {
         let image_file = PathBuf::from("images/test/test_01.jpg");
         let result = load(&image_file);
         assert!(result.is_ok());
-        let (_, fmt) = result.unwrap();
+        let (_, fmt, metadata) = result.unwrap();
         assert_eq!(fmt, ImageFormat::Jpeg);
+        assert_eq!(metadata.get("model"), Some("X100V"));
+    }
+
+    #[test]
+    fn load_applies_exif_orientation() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let path = temp_dir.path().join("portrait.jpg");
+        // A landscape sensor image of a portrait scene, with its top row red.
+        let pixels = RgbImage::from_fn(40, 20, |x, _| {
+            if x < 10 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        save_jpeg_with_orientation(&path, &pixels, 6);
+
+        let (img, _, _) = load(&path).unwrap();
+        assert_eq!(img.dimensions(), (20, 40));
+        // Rotated 90° clockwise, so the red left column is now at the top.
+        let top = img.get_pixel(10, 2);
+        assert!(top[0] > 200 && top[2] < 50, "{top:?}");
+        let bottom = img.get_pixel(10, 37);
+        assert!(bottom[2] > 200 && bottom[0] < 50, "{bottom:?}");
     }
 
     #[test]
@@ -115,30 +326,131 @@ mod tests {
         );
     }
 
+    #[test]
+    fn border_grows_to_fit_shadow() {
+        let input_size = Size::from((100, 100));
+        let config = FrameConfig::default();
+        let border = border(&input_size, &config);
+        assert_eq!(border.left(), 5);
+
+        let config = config.with_shadow(Some(Shadow::from_str("10 0 4").unwrap()));
+        let border = super::border(&input_size, &config);
+        assert_eq!(border.top(), 5);
+        assert_eq!(border.right(), 14);
+        assert_eq!(border.bottom(), 5);
+        assert_eq!(border.left(), 5);
+
+        let keyline = Keyline::new(Length::Pixels(4.), Length::Pixels(4.), Rgba([0; 4]));
+        let config = config.with_keyline(Some(keyline));
+        let border = super::border(&input_size, &config);
+        assert_eq!(border.top(), 8);
+        assert_eq!(border.right(), 14);
+
+        let mats = vec![Mat::from_str("10:black").unwrap()];
+        let config = FrameConfig::default().with_mats(mats);
+        let border = super::border(&input_size, &config);
+        assert_eq!(border, Border::new(15, 15, 15, 15));
+    }
+
+    #[test]
+    fn border_measured_against_output() {
+        let input_size = Size::from((100, 100));
+        let mats = vec![Mat::from_str("10:black").unwrap()];
+        let config = FrameConfig::default()
+            .with_mats(mats)
+            .with_margin_basis(MarginBasis::Output);
+        // The mat and the margins take up 30% of the output's sides together, so it is 142px wide.
+        assert!((margin_dimension(&input_size, &config) - 100. / 0.7).abs() < 0.01);
+        assert_eq!(border(&input_size, &config), Border::new(21, 21, 21, 21));
+
+        let config = config.with_size(Some(Size::new(200, 200)));
+        assert_eq!(margin_dimension(&input_size, &config), 200.);
+        assert_eq!(border(&input_size, &config), Border::new(30, 30, 30, 30));
+    }
+
+    #[test]
+    fn border_grows_to_fit_caption() {
+        let input_size = Size::from((100, 100));
+        let font =
+            Caption::load_font(&PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")).unwrap();
+        let caption = Caption::new(
+            Template::from_str("Caption").unwrap(),
+            font,
+            Length::Pixels(20.),
+            Rgba([0, 0, 0, 255]),
+            RelativePosition::default(),
+            Side::Bottom,
+        );
+        let config = FrameConfig::default().with_caption(Some(caption.clone()));
+        assert_eq!(border(&input_size, &config), Border::new(5, 5, 5, 5));
+
+        let config = FrameConfig::default().with_caption(Some(caption.clone().with_grow(true)));
+        assert_eq!(border(&input_size, &config), Border::new(5, 5, 35, 5));
+
+        // The caption is laid out outside the mats, so it adds to them and the margins.
+        let config = config.with_mats(vec![Mat::from_str("10:black").unwrap()]);
+        assert_eq!(
+            inner_border(&input_size, &config),
+            Border::new(10, 10, 10, 10)
+        );
+        assert_eq!(border(&input_size, &config), Border::new(15, 15, 45, 15));
+    }
+
+    #[test]
+    fn fit_image_to_size() {
+        let config = FrameConfig::default().with_size(Some(Size::new(200, 200)));
+        let fitted = fit_to_size(&Size::new(1000, 500), &Size::new(200, 200), &config).unwrap();
+        assert_eq!(fitted, Size::new(186, 93));
+        assert_eq!(border(&fitted, &config), Border::new(7, 7, 7, 7));
+
+        // Small images are scaled up to fill the output.
+        let fitted = fit_to_size(&Size::new(10, 20), &Size::new(200, 200), &config).unwrap();
+        assert_eq!(fitted, Size::new(93, 186));
+
+        let config = FrameConfig::new(
+            None,
+            Fill::Solid(Rgba([0, 0, 0, 0])),
+            None,
+            Margins::from_str("100px").unwrap(),
+            RelativePosition::default(),
+        );
+        assert!(fit_to_size(&Size::new(10, 20), &Size::new(200, 200), &config).is_none());
+    }
+
     #[test]
     fn output_dimensions_with_border() {
         let input_size = Size::from((100, 100));
         let border = Margins::from_str("10")
             .unwrap()
-            .to_border_with_size(&input_size);
+            .to_border(MarginBasis::default().dimension(&input_size));
         let config = FrameConfig::default();
-        let output_size = output_dimensions(&input_size, &border, &config);
+        let output_size = output_dimensions(&input_size, &border, &config).unwrap();
         assert_eq!(output_size, Size::from((120, 120)));
     }
 
+    #[test]
+    fn oversized_output_dimensions_are_err() {
+        let input_size = Size::from((100, 100));
+        let border = Border::new(u32::MAX, u32::MAX, u32::MAX, u32::MAX);
+        assert!(output_dimensions(&input_size, &border, &FrameConfig::default()).is_err());
+
+        let border = Border::new(0, 40_000, 0, 40_000);
+        assert!(output_dimensions(&input_size, &border, &FrameConfig::default()).is_err());
+    }
+
     #[test]
     fn output_dimensions_with_tall_ar() {
         let input_size = Size::from((100, 100));
         let margins = Margins::from_str("10").unwrap();
-        let border = margins.to_border_with_size(&input_size);
+        let border = margins.to_border(MarginBasis::default().dimension(&input_size));
         let config = FrameConfig::new(
             Some(AspectRatio::from_str("9:16").unwrap()),
-            Rgba([0, 0, 0, 0]),
+            Fill::Solid(Rgba([0, 0, 0, 0])),
             None,
             margins,
             RelativePosition::default(),
         );
-        let output_size = output_dimensions(&input_size, &border, &config);
+        let output_size = output_dimensions(&input_size, &border, &config).unwrap();
         assert_eq!(output_size, Size::from((120, 213)));
     }
 
@@ -146,25 +458,82 @@ mod tests {
     fn output_dimensions_with_wide_ar() {
         let input_size = Size::from((100, 100));
         let margins = Margins::from_str("10").unwrap();
-        let border = margins.to_border_with_size(&input_size);
+        let border = margins.to_border(MarginBasis::default().dimension(&input_size));
         let config = FrameConfig::new(
             Some(AspectRatio::from_str("16:9").unwrap()),
-            Rgba([0, 0, 0, 0]),
+            Fill::Solid(Rgba([0, 0, 0, 0])),
             None,
             margins,
             RelativePosition::default(),
         );
-        let output_size = output_dimensions(&input_size, &border, &config);
+        let output_size = output_dimensions(&input_size, &border, &config).unwrap();
         assert_eq!(output_size, Size::from((213, 120)));
     }
 
+    #[test]
+    fn output_dimensions_with_orientation_aware_ar() {
+        let margins = Margins::from_str("10").unwrap();
+        let config = FrameConfig::new(
+            Some(AspectRatio::from_str("4:5~").unwrap()),
+            Fill::Solid(Rgba([0, 0, 0, 0])),
+            None,
+            margins.clone(),
+            RelativePosition::default(),
+        );
+        let landscape = Size::from((200, 100));
+        let border = margins.to_border(MarginBasis::default().dimension(&landscape));
+        let output_size = output_dimensions(&landscape, &border, &config).unwrap();
+        assert_eq!(output_size, Size::from((230, 184)));
+
+        let portrait = Size::from((100, 200));
+        let border = margins.to_border(MarginBasis::default().dimension(&portrait));
+        let output_size = output_dimensions(&portrait, &border, &config).unwrap();
+        assert_eq!(output_size, Size::from((184, 230)));
+    }
+
+    #[test]
+    fn crop_image_to_ratio() {
+        let config = FrameConfig::default();
+        let input_size = Size::from((1000, 500));
+        let ratio = AspectRatio::from_str("1:1").unwrap();
+        let crop = crop_to_ratio(&input_size, &ratio, &config);
+        assert_eq!(crop, Size::from((500, 500)));
+
+        // The crop and its 5% margins make up the ratio, with margins of the same thickness.
+        let ratio = AspectRatio::from_str("4:5").unwrap();
+        let crop = crop_to_ratio(&input_size, &ratio, &config);
+        let border = border(&crop, &config);
+        assert_eq!(border.left(), border.top());
+        let output_size = size_with_border(&crop, &border);
+        assert!((output_size.width() as f32 / output_size.height() as f32 - 0.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn crop_position_in_image() {
+        let input_size = Size::from((300, 100));
+        let crop = Size::from((100, 100));
+        let position = |relative: &str, focus: Option<&str>| {
+            crop_position(
+                &input_size,
+                &crop,
+                &RelativePosition::from_str(relative).unwrap(),
+                focus.map(|f| FocusPoint::from_str(f).unwrap()).as_ref(),
+            )
+        };
+        assert_eq!(position("center", None), Point::new(100, 0));
+        assert_eq!(position("left", None), Point::new(0, 0));
+        assert_eq!(position("right", None), Point::new(200, 0));
+        assert_eq!(position("left", Some("75,50")), Point::new(175, 0));
+        assert_eq!(position("left", Some("95,50")), Point::new(200, 0));
+    }
+
     #[test]
     fn position_in_frame() {
         let img_size = Size::from((100, 100));
         let output_size = Size::from((200, 200));
         let border = Margins::from_str("10")
             .unwrap()
-            .to_border_with_size(&img_size);
+            .to_border(MarginBasis::default().dimension(&img_size));
 
         let relative_position = RelativePosition::from_str("center").unwrap();
         let pos = position(&img_size, &output_size, &relative_position, &border);
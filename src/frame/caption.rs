@@ -0,0 +1,326 @@
+//! The caption module is responsible for rendering a line of text in the frame margin.
+use crate::errors::OliframeError;
+use crate::frame::Template;
+use crate::frame::composite::{self, Blending};
+use crate::frame::metadata::Metadata;
+use crate::frame::outline::Outline;
+use crate::geometry::{
+    Border, HorizontalPosition, Length, RelativePosition, Side, VerticalPosition,
+};
+use ab_glyph::{Font, FontArc, PxScale, ScaleFont, point};
+use derive_getters::Getters;
+use image::{ImageBuffer, Luma, Rgba, RgbaImage, imageops};
+use std::path::PathBuf;
+
+/// The space kept between the text and the edges of the margin, as a fraction of the text height.
+const PADDING: f32 = 0.25;
+
+/// The coverage (0 to 1) of each pixel of the margin by the text.
+type Coverage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+/// A single line of text rendered in one of the margins around the image.
+///
+/// The caption is laid out in the strip of margin alongside the chosen side of the image,
+/// outside any mats, keyline and shadow, so it never overlaps them or the image.  A relative size is resolved against the thickness of that
+/// margin.  Text that does not fit is scaled down, unless the margin is allowed to grow.
+#[derive(Clone, Debug, Getters)]
+pub struct Caption {
+    /// The text of the caption, filled from each image's metadata.
+    text: Template,
+    /// The font used to render the text.
+    font: FontArc,
+    /// The height of the text.
+    size: Length,
+    /// The color of the text.
+    color: Rgba<u8>,
+    /// The alignment of the text within the margin, read in the direction of the text.
+    position: RelativePosition,
+    /// The margin in which the text is rendered.
+    side: Side,
+    /// Whether the margin grows to fit text sized in pixels.
+    grow: bool,
+}
+
+impl Caption {
+    /// Create a new caption.
+    pub fn new(
+        text: Template,
+        font: FontArc,
+        size: Length,
+        color: Rgba<u8>,
+        position: RelativePosition,
+        side: Side,
+    ) -> Self {
+        Self {
+            text,
+            font,
+            size,
+            color,
+            position,
+            side,
+            grow: false,
+        }
+    }
+
+    /// Set whether the margin grows to fit text sized in pixels.
+    pub fn with_grow(mut self, grow: bool) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// Load a TrueType or OpenType font from the given file.
+    pub fn load_font(path: &PathBuf) -> Result<FontArc, OliframeError> {
+        let unreadable =
+            || OliframeError::InvalidInput(format!("Unable to read font file: {}", path.display()));
+        let data = std::fs::read(path).map_err(|_| unreadable())?;
+        FontArc::try_from_vec(data).map_err(|_| unreadable())
+    }
+
+    /// The space added to the chosen margin to fit the text and its padding.
+    ///
+    /// Only text sized in pixels can grow the margin: relative sizes always fit its thickness.
+    pub fn extent(&self) -> Border {
+        match (self.grow, &self.size) {
+            (true, Length::Pixels(px)) => {
+                Border::on_side(self.side, (px * (1. + 2. * PADDING)).ceil() as u32)
+            }
+            _ => Border::default(),
+        }
+    }
+
+    /// Paint the caption, filled from the image's metadata, onto the canvas,
+    /// in the margin alongside the image window, outside the inset taken up by mats,
+    /// the keyline and the shadow.
+    pub fn paint(
+        &self,
+        canvas: &mut RgbaImage,
+        window: &Outline,
+        inset: &Border,
+        metadata: &Metadata,
+        blending: Blending,
+    ) {
+        let (width, height) = canvas.dimensions();
+        let (left, top, right, bottom) = window.bounds(width, height);
+        // The strip of margin as (x, y, length along the image, thickness).
+        let (x, y, along, thickness) = match self.side {
+            Side::Top => (left, 0, right - left, top.saturating_sub(inset.top())),
+            Side::Right => {
+                let x = right.saturating_add(inset.right()).min(width);
+                (x, top, bottom - top, width - x)
+            }
+            Side::Bottom => {
+                let y = bottom.saturating_add(inset.bottom()).min(height);
+                (left, y, right - left, height - y)
+            }
+            Side::Left => (0, top, bottom - top, left.saturating_sub(inset.left())),
+        };
+
+        let text = self.text.render(metadata);
+        let Some(coverage) = self.render(&text, along, thickness) else {
+            return;
+        };
+        // Text in the side margins runs along the image, with the baseline facing it.
+        let coverage = match self.side {
+            Side::Left => imageops::rotate270(&coverage),
+            Side::Right => imageops::rotate90(&coverage),
+            Side::Top | Side::Bottom => coverage,
+        };
+
+        for (dx, dy, pixel) in coverage.enumerate_pixels() {
+            if pixel[0] > 0. {
+                composite::over(
+                    canvas.get_pixel_mut(x + dx, y + dy),
+                    &self.color,
+                    pixel[0],
+                    blending,
+                );
+            }
+        }
+    }
+
+    /// Render the text's coverage of a horizontal strip of the given length and thickness.
+    fn render(&self, text: &str, along: u32, thickness: u32) -> Option<Coverage> {
+        let mut size = self.size.to_pixels(thickness as f32) as f32;
+        if text.is_empty() || along == 0 || thickness == 0 || size == 0. {
+            return None;
+        }
+
+        let fit = (along as f32 / self.advance(text, size))
+            .min(thickness as f32 / (size * (1. + 2. * PADDING)))
+            .min(1.);
+        if fit < 1. {
+            log::warn!(
+                "The caption \"{}\" does not fit in the {} margin and was scaled down.",
+                text,
+                self.side.as_ref()
+            );
+            size *= fit;
+        }
+
+        let font = self.font.as_scaled(PxScale::from(size));
+        let (along, thickness) = (along as f32, thickness as f32);
+        let text_width = self.advance(text, size);
+        let padding = size * PADDING;
+        let mut caret = match self.position.horizontal() {
+            HorizontalPosition::Left => 0.,
+            HorizontalPosition::Center => (along - text_width) / 2.,
+            HorizontalPosition::Right => along - text_width,
+        };
+        let text_top = match self.position.vertical() {
+            VerticalPosition::Top => padding,
+            VerticalPosition::Center => (thickness - size) / 2.,
+            VerticalPosition::Bottom => thickness - size - padding,
+        };
+
+        let mut coverage = Coverage::new(along as u32, thickness as u32);
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            let glyph =
+                id.with_scale_and_position(font.scale(), point(caret, text_top + font.ascent()));
+            caret += font.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, c| {
+                let px = bounds.min.x as i64 + gx as i64;
+                let py = bounds.min.y as i64 + gy as i64;
+                if px >= 0 && py >= 0 && px < along as i64 && py < thickness as i64 {
+                    let pixel = coverage.get_pixel_mut(px as u32, py as u32);
+                    pixel[0] = (pixel[0] + c).min(1.);
+                }
+            });
+        }
+
+        Some(coverage)
+    }
+
+    /// The width of the text, including kerning, at the given size.
+    fn advance(&self, text: &str, size: f32) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let mut previous = None;
+        text.chars()
+            .map(|c| {
+                let id = font.glyph_id(c);
+                let kern = previous.map_or(0., |previous| font.kern(previous, id));
+                previous = Some(id);
+                kern + font.h_advance(id)
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::paint_around_window;
+    use std::str::FromStr;
+
+    /// A caption in the fixture font, in black.
+    fn caption(text: &str, size: Length, position: &str, side: Side) -> Caption {
+        let font =
+            Caption::load_font(&PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")).unwrap();
+        Caption::new(
+            Template::from_str(text).unwrap(),
+            font,
+            size,
+            Rgba([0, 0, 0, 255]),
+            RelativePosition::from_str(position).unwrap(),
+            side,
+        )
+    }
+
+    /// Paint the caption around the test window, outside the given inset,
+    /// returning the painted pixels.
+    fn paint(caption: &Caption, inset: &Border) -> Vec<(u32, u32)> {
+        paint_around_window(|canvas, window| {
+            caption.paint(canvas, window, inset, &Metadata::default(), Blending::Srgb)
+        })
+    }
+
+    #[test]
+    fn load_fonts() {
+        assert!(Caption::load_font(&PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")).is_ok());
+        assert!(Caption::load_font(&PathBuf::from("Cargo.toml")).is_err());
+        assert!(Caption::load_font(&PathBuf::from("missing.ttf")).is_err());
+    }
+
+    #[test]
+    fn caption_extent() {
+        let fixed = caption("Hi", Length::Pixels(20.), "center", Side::Bottom);
+        assert_eq!(fixed.extent(), Border::default());
+        assert_eq!(
+            fixed.clone().with_grow(true).extent(),
+            Border::new(0, 0, 30, 0)
+        );
+        let relative = caption("Hi", Length::Relative(0.5), "center", Side::Left).with_grow(true);
+        assert_eq!(relative.extent(), Border::default());
+    }
+
+    #[test]
+    fn caption_is_painted_below_the_image() {
+        let painted = paint(
+            &caption("Hello", Length::Relative(0.5), "center", Side::Bottom),
+            &Border::default(),
+        );
+        assert!(!painted.is_empty());
+        assert!(painted.iter().all(|(_, y)| *y >= 80));
+        // Centered horizontally under the image.
+        let left = painted.iter().map(|(x, _)| *x).min().unwrap();
+        let right = painted.iter().map(|(x, _)| *x).max().unwrap();
+        assert!(left > 40 && right < 100 && (left + right).abs_diff(140) <= 2);
+    }
+
+    #[test]
+    fn caption_never_overlaps_the_image() {
+        // Text far too long and too large for the margin is scaled down to fit.
+        for side in [Side::Top, Side::Right, Side::Bottom, Side::Left] {
+            for position in ["top left", "center", "bottom right"] {
+                let text = "A caption much longer than the image is wide";
+                let painted = paint(
+                    &caption(text, Length::Pixels(50.), position, side),
+                    &Border::default(),
+                );
+                assert!(!painted.is_empty(), "{side:?} {position}");
+                assert!(
+                    painted
+                        .iter()
+                        .all(|(x, y)| !((20..120).contains(x) && (20..80).contains(y))),
+                    "{side:?} {position}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn caption_is_painted_outside_the_inset() {
+        let caption = caption("Hello", Length::Relative(0.5), "center", Side::Bottom);
+        let painted = paint(&caption, &Border::new(10, 10, 10, 10));
+        assert!(!painted.is_empty());
+        assert!(painted.iter().all(|(_, y)| *y >= 90));
+
+        // A margin taken up entirely by the inset leaves no room for the caption.
+        let painted = paint(&caption, &Border::new(0, 0, 40, 0));
+        assert!(painted.is_empty());
+    }
+
+    #[test]
+    fn side_captions_run_along_the_image() {
+        let painted = paint(
+            &caption("Side", Length::Pixels(10.), "center", Side::Left),
+            &Border::default(),
+        );
+        assert!(painted.iter().all(|(x, y)| *x < 20 && (20..80).contains(y)));
+        let height = painted.iter().map(|(_, y)| *y).max().unwrap()
+            - painted.iter().map(|(_, y)| *y).min().unwrap();
+        let width = painted.iter().map(|(x, _)| *x).max().unwrap()
+            - painted.iter().map(|(x, _)| *x).min().unwrap();
+        assert!(height > width);
+    }
+}
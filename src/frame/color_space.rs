@@ -0,0 +1,227 @@
+//! The color space module is responsible for matching colors given in sRGB
+//! to images tagged with another ICC color profile.
+use image::{DynamicImage, Rgba, RgbaImage};
+use moxcms::{
+    ColorProfile, DataColorSpace, Layout, ProfileText, Transform8BitExecutor, TransformOptions,
+};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// The number of pixels converted at a time.
+const CHUNK: usize = 4096;
+
+/// The color space of an image tagged with an ICC profile other than sRGB.
+///
+/// Frame colors are given in sRGB, so they are converted into the image's color space
+/// to look the same next to the image as they would in an untagged image.
+#[derive(Clone)]
+pub struct ColorSpace {
+    /// The name of the color space, as given by its profile.
+    name: String,
+    /// The conversion from sRGB into the color space.
+    from_srgb: Arc<Transform8BitExecutor>,
+    /// The conversion from the color space to sRGB.
+    to_srgb: Arc<Transform8BitExecutor>,
+}
+
+impl ColorSpace {
+    /// Read the color space described by an ICC profile.
+    ///
+    /// Returns `None` for profiles that cannot be read or that are not RGB,
+    /// and for those that match sRGB, since their colors need no conversion.
+    pub fn from_icc(icc: &[u8]) -> Option<Self> {
+        let profile = ColorProfile::new_from_slice(icc)
+            .inspect_err(|e| log::warn!("Unable to read the ICC profile: {e}"))
+            .ok()?;
+        if profile.color_space != DataColorSpace::Rgb {
+            return None;
+        }
+        let srgb = ColorProfile::new_srgb();
+        let options = TransformOptions::default();
+        let from_srgb = srgb
+            .create_transform_8bit(Layout::Rgba, &profile, Layout::Rgba, options)
+            .ok()?;
+        let to_srgb = profile
+            .create_transform_8bit(Layout::Rgba, &srgb, Layout::Rgba, options)
+            .ok()?;
+
+        let color_space = Self {
+            name: match &profile.description {
+                Some(ProfileText::PlainString(text)) => text.clone(),
+                Some(ProfileText::Localizable(texts)) => texts
+                    .first()
+                    .map(|text| text.value.clone())
+                    .unwrap_or_default(),
+                Some(ProfileText::Description(text)) => text.ascii_string.clone(),
+                None => String::new(),
+            },
+            from_srgb,
+            to_srgb,
+        };
+        (!color_space.is_srgb()).then_some(color_space)
+    }
+
+    /// Convert a color from sRGB into the color space.
+    pub fn color_from_srgb(&self, color: Rgba<u8>) -> Rgba<u8> {
+        let mut converted = color;
+        convert(self.from_srgb.as_ref(), &mut converted.0);
+        converted
+    }
+
+    /// Convert every pixel of an image from sRGB into the color space.
+    pub fn image_from_srgb(&self, image: &mut RgbaImage) {
+        convert(self.from_srgb.as_ref(), image);
+    }
+
+    /// Convert every pixel of an image from the color space to sRGB.
+    pub fn image_to_srgb(&self, image: &mut RgbaImage) {
+        convert(self.to_srgb.as_ref(), image);
+    }
+
+    /// Convert an image of any pixel format from the color space to sRGB.
+    pub fn dynamic_to_srgb(&self, image: &DynamicImage) -> DynamicImage {
+        let mut converted = image.to_rgba8();
+        self.image_to_srgb(&mut converted);
+        DynamicImage::ImageRgba8(converted)
+    }
+
+    /// Whether the color space matches sRGB, within rounding,
+    /// judged by converting a grid of colors spanning the sRGB gamut.
+    fn is_srgb(&self) -> bool {
+        let steps = [0, 64, 128, 192, 255];
+        steps.iter().all(|&r| {
+            steps.iter().all(|&g| {
+                steps.iter().all(|&b| {
+                    let color = Rgba([r, g, b, 255]);
+                    let converted = self.color_from_srgb(color);
+                    color
+                        .0
+                        .iter()
+                        .zip(converted.0)
+                        .all(|(a, b)| a.abs_diff(b) <= 1)
+                })
+            })
+        })
+    }
+}
+
+impl Debug for ColorSpace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColorSpace")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Whether an ICC profile describes RGB colors, as its header says.
+pub fn is_rgb(icc: &[u8]) -> bool {
+    icc.get(16..20) == Some(b"RGB ")
+}
+
+/// Convert an image tagged with an ICC profile that does not describe RGB colors to sRGB.
+///
+/// Gray images are converted through their profile.
+/// Images in other color spaces, such as CMYK, have already been turned into RGB by their decoder,
+/// so their pixels are taken as they are.
+pub fn to_srgb(icc: &[u8], image: DynamicImage) -> DynamicImage {
+    let Ok(profile) = ColorProfile::new_from_slice(icc) else {
+        return image;
+    };
+    if profile.color_space != DataColorSpace::Gray {
+        return image;
+    }
+    let transform = profile.create_transform_8bit(
+        Layout::GrayAlpha,
+        &ColorProfile::new_srgb(),
+        Layout::Rgba,
+        TransformOptions::default(),
+    );
+    let transform = match transform {
+        Ok(transform) => transform,
+        Err(e) => {
+            log::warn!("Unable to convert colors: {e}");
+            return image;
+        }
+    };
+
+    let gray = image.to_luma_alpha8();
+    let mut converted = RgbaImage::new(gray.width(), gray.height());
+    for (source, target) in gray.chunks(CHUNK * 2).zip(converted.chunks_mut(CHUNK * 4)) {
+        if let Err(e) = transform.transform(source, target) {
+            log::warn!("Unable to convert colors: {e}");
+            return image;
+        }
+    }
+    DynamicImage::ImageRgba8(converted)
+}
+
+/// Convert RGBA pixels in place, a chunk at a time.
+fn convert(transform: &Transform8BitExecutor, pixels: &mut [u8]) {
+    let mut source = Vec::with_capacity(CHUNK * 4);
+    for chunk in pixels.chunks_mut(CHUNK * 4) {
+        source.clear();
+        source.extend_from_slice(chunk);
+        if let Err(e) = transform.transform(&source, chunk) {
+            log::warn!("Unable to convert colors: {e}");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Display P3 profile, encoded as ICC data.
+    fn display_p3() -> Vec<u8> {
+        ColorProfile::new_display_p3().encode().unwrap()
+    }
+
+    #[test]
+    fn srgb_profiles_need_no_conversion() {
+        let srgb = ColorProfile::new_srgb().encode().unwrap();
+        assert!(ColorSpace::from_icc(&srgb).is_none());
+        assert!(ColorSpace::from_icc(b"not a profile").is_none());
+        assert!(ColorSpace::from_icc(&display_p3()).is_some());
+    }
+
+    #[test]
+    fn convert_gray_images_to_srgb() {
+        let gray = ColorProfile::new_gray_with_gamma(2.2).encode().unwrap();
+        assert!(!is_rgb(&gray));
+        assert!(is_rgb(&display_p3()));
+
+        let image =
+            DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([128])));
+        let converted = to_srgb(&gray, image).to_rgba8();
+        let pixel = converted.get_pixel(1, 1);
+        assert_eq!(pixel[0], pixel[1]);
+        assert_eq!(pixel[1], pixel[2]);
+        assert_eq!(pixel[3], 255);
+        // A gamma of 2.2 is close to, but not quite, the sRGB curve.
+        assert!(pixel[0].abs_diff(128) <= 4);
+    }
+
+    #[test]
+    fn convert_colors_into_display_p3() {
+        let p3 = ColorSpace::from_icc(&display_p3()).unwrap();
+        // Neutral colors are unchanged, since both spaces share the same white point and curve.
+        let gray = Rgba([128, 128, 128, 255]);
+        assert!(
+            p3.color_from_srgb(gray)
+                .0
+                .iter()
+                .zip(gray.0)
+                .all(|(a, b)| a.abs_diff(b) <= 1)
+        );
+        // Saturated sRGB red lies inside the wider P3 gamut, so it is less saturated there.
+        let red = p3.color_from_srgb(Rgba([255, 0, 0, 128]));
+        assert!(red[0] < 255 && red[1] > 0);
+        assert_eq!(red[3], 128);
+
+        let mut image = RgbaImage::from_pixel(3, 2, red);
+        p3.image_to_srgb(&mut image);
+        let back = image.get_pixel(2, 1);
+        assert!(back[0] >= 254 && back[1] <= 1 && back[2] <= 1);
+    }
+}
@@ -0,0 +1,242 @@
+//! The composite module is responsible for blending painted layers onto the frame.
+use image::{Rgba, RgbaImage};
+use strum_macros::{AsRefStr, EnumString};
+
+/// What happens to transparent and semi-transparent pixels of the input image.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Transparency {
+    /// The frame's fill shows through transparent pixels of the image.
+    #[default]
+    Flatten,
+    /// Transparent pixels of the image stay transparent in the output.
+    Keep,
+}
+
+/// The color space in which colors are blended.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Blending {
+    /// Colors are blended on their 8-bit sRGB values.
+    #[default]
+    Srgb,
+    /// Colors are converted to linear light, blended, and converted back to sRGB.
+    Linear,
+}
+
+impl Blending {
+    /// Convert an 8-bit sRGB channel to the blending space, on a 0 to 255 scale.
+    fn decode(&self, channel: u8) -> f32 {
+        match self {
+            Blending::Srgb => channel as f32,
+            Blending::Linear => srgb_to_linear(channel as f32 / 255.) * 255.,
+        }
+    }
+
+    /// Convert a channel from the blending space, on a 0 to 255 scale, back to 8-bit sRGB.
+    fn encode(&self, value: f32) -> u8 {
+        match self {
+            Blending::Srgb => value.round() as u8,
+            Blending::Linear => (linear_to_srgb(value / 255.) * 255.).round() as u8,
+        }
+    }
+
+    /// Interpolate between two colors, moving from the first to the second by the given weight (0 to 1).
+    pub fn interpolate(&self, from: &Rgba<u8>, to: &Rgba<u8>, weight: f32) -> Rgba<u8> {
+        let mut color = Rgba([0, 0, 0, 0]);
+        for channel in 0..3 {
+            let (from, to) = (self.decode(from[channel]), self.decode(to[channel]));
+            color[channel] = self.encode(from + (to - from) * weight);
+        }
+        let (from, to) = (from[3] as f32, to[3] as f32);
+        color[3] = (from + (to - from) * weight).round() as u8;
+        color
+    }
+}
+
+/// Convert an sRGB value (0 to 1) to linear light.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light value (0 to 1) to sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0., 1.);
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Paint a color over a pixel (Porter-Duff "over"), with the given coverage (0 to 1) of the pixel.
+pub fn over(pixel: &mut Rgba<u8>, color: &Rgba<u8>, coverage: f32, blending: Blending) {
+    let alpha = coverage * color[3] as f32 / 255.;
+    if alpha <= 0. {
+        return;
+    } else if alpha >= 1. {
+        *pixel = *color;
+        return;
+    }
+    let below = pixel[3] as f32 / 255. * (1. - alpha);
+    let out_alpha = alpha + below;
+    for channel in 0..3 {
+        pixel[channel] = blending.encode(
+            (blending.decode(color[channel]) * alpha + blending.decode(pixel[channel]) * below)
+                / out_alpha,
+        );
+    }
+    pixel[3] = (out_alpha * 255.).round() as u8;
+}
+
+/// Mix two pixels, weighting the first by the given fraction (0 to 1), with premultiplied alpha
+/// so the color of transparent pixels does not bleed into the result.
+pub fn mix(first: &Rgba<u8>, second: &Rgba<u8>, fraction: f32, blending: Blending) -> Rgba<u8> {
+    let first_alpha = first[3] as f32 / 255. * fraction;
+    let second_alpha = second[3] as f32 / 255. * (1. - fraction);
+    let out_alpha = first_alpha + second_alpha;
+    if out_alpha <= 0. {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let mut pixel = Rgba([0, 0, 0, (out_alpha * 255.).round() as u8]);
+    for channel in 0..3 {
+        pixel[channel] = blending.encode(
+            (blending.decode(first[channel]) * first_alpha
+                + blending.decode(second[channel]) * second_alpha)
+                / out_alpha,
+        );
+    }
+    pixel
+}
+
+/// Flatten an image onto a solid matte color, leaving every pixel fully opaque.
+pub fn flatten(image: &RgbaImage, matte: &Rgba<u8>, blending: Blending) -> RgbaImage {
+    let matte = Rgba([matte[0], matte[1], matte[2], 255]);
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let mut pixel = matte;
+        over(&mut pixel, image.get_pixel(x, y), 1., blending);
+        pixel
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_over_pixel() {
+        let mut pixel = Rgba([255, 255, 255, 255]);
+        over(&mut pixel, &Rgba([0, 0, 0, 255]), 0., Blending::Srgb);
+        assert_eq!(pixel, Rgba([255, 255, 255, 255]));
+
+        over(&mut pixel, &Rgba([0, 0, 0, 255]), 0.5, Blending::Srgb);
+        assert_eq!(pixel, Rgba([128, 128, 128, 255]));
+
+        over(&mut pixel, &Rgba([0, 0, 0, 255]), 1., Blending::Srgb);
+        assert_eq!(pixel, Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn paint_translucent_color_over_transparent_pixel() {
+        let mut pixel = Rgba([0, 0, 0, 0]);
+        over(&mut pixel, &Rgba([255, 0, 0, 128]), 1., Blending::Srgb);
+        assert_eq!(pixel, Rgba([255, 0, 0, 128]));
+    }
+
+    #[test]
+    fn mix_pixels_with_premultiplied_alpha() {
+        let red = Rgba([255, 0, 0, 255]);
+        let clear_green = Rgba([0, 255, 0, 0]);
+        assert_eq!(
+            mix(&red, &Rgba([0, 0, 255, 255]), 0.5, Blending::Srgb),
+            Rgba([128, 0, 128, 255])
+        );
+        // The color of a fully transparent pixel does not tint the result.
+        assert_eq!(
+            mix(&red, &clear_green, 0.5, Blending::Srgb),
+            Rgba([255, 0, 0, 128])
+        );
+        assert_eq!(
+            mix(&clear_green, &clear_green, 0.5, Blending::Srgb),
+            Rgba([0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn parse_transparency() {
+        assert_eq!(
+            "flatten".parse::<Transparency>().unwrap(),
+            Transparency::Flatten
+        );
+        assert_eq!("Keep".parse::<Transparency>().unwrap(), Transparency::Keep);
+        assert!("drop".parse::<Transparency>().is_err());
+    }
+
+    #[test]
+    fn flatten_onto_matte() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 0]));
+        let flat = flatten(&image, &Rgba([0, 0, 255, 255]), Blending::Srgb);
+        assert_eq!(flat.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(flat.get_pixel(1, 0), &Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn srgb_round_trip() {
+        for value in 0..=255 {
+            let srgb = value as f32 / 255.;
+            let round_trip = linear_to_srgb(srgb_to_linear(srgb));
+            assert!((round_trip - srgb).abs() < 1e-4);
+        }
+        assert!((srgb_to_linear(0.5) - 0.2140).abs() < 1e-4);
+        assert!((linear_to_srgb(0.5) - 0.7354).abs() < 1e-4);
+    }
+
+    #[test]
+    fn paint_over_pixel_in_linear_light() {
+        // Half of black over white is half as much light: 0.5 in linear light, 188 in sRGB.
+        let mut pixel = Rgba([255, 255, 255, 255]);
+        over(&mut pixel, &Rgba([0, 0, 0, 255]), 0.5, Blending::Linear);
+        assert_eq!(pixel, Rgba([188, 188, 188, 255]));
+
+        let mut pixel = Rgba([255, 0, 0, 255]);
+        over(&mut pixel, &Rgba([0, 255, 0, 255]), 0.25, Blending::Linear);
+        let expected = |light: f32| (linear_to_srgb(light) * 255.).round() as u8;
+        assert_eq!(pixel, Rgba([expected(0.75), expected(0.25), 0, 255]));
+    }
+
+    #[test]
+    fn mix_pixels_in_linear_light() {
+        let mixed = mix(
+            &Rgba([255, 255, 255, 255]),
+            &Rgba([0, 0, 0, 255]),
+            0.5,
+            Blending::Linear,
+        );
+        assert_eq!(mixed, Rgba([188, 188, 188, 255]));
+    }
+
+    #[test]
+    fn interpolate_colors() {
+        let (black, white) = (Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 0]));
+        assert_eq!(
+            Blending::Srgb.interpolate(&black, &white, 0.5),
+            Rgba([128, 128, 128, 128])
+        );
+        assert_eq!(
+            Blending::Linear.interpolate(&black, &white, 0.5),
+            Rgba([188, 188, 188, 128])
+        );
+    }
+
+    #[test]
+    fn parse_blending() {
+        assert_eq!("srgb".parse::<Blending>().unwrap(), Blending::Srgb);
+        assert_eq!("Linear".parse::<Blending>().unwrap(), Blending::Linear);
+        assert!("gamma".parse::<Blending>().is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//! The encode module is responsible for encoding output images along with their metadata.
+use crate::frame::metadata::{
+    ICC_TAG, KeepMetadata, Metadata, XMP_JPEG_PREFIX, XMP_PNG_KEYWORD, XMP_TAG,
+};
+use crate::geometry::Size;
+use exif::experimental::Writer;
+use exif::{Context, Field, In, Tag, Value};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, GenericImageView, ImageEncoder, ImageFormat};
+use img_parts::Bytes;
+use img_parts::jpeg::{Jpeg, JpegSegment, markers};
+use img_parts::png::{Png, PngChunk};
+use std::io::Cursor;
+
+/// The prefix of the JPEG APP1 segment holding EXIF data.
+const EXIF_JPEG_PREFIX: &[u8] = b"Exif\0\0";
+/// The TIFF tag describing the meaning of the samples beyond the color channels.
+const EXTRA_SAMPLES_TAG: Tag = Tag(Context::Tiff, 338);
+
+/// Encode the image in the given format, along with the metadata kept from the input.
+///
+/// EXIF, XMP and ICC data are written to JPEG, PNG, WebP and TIFF files.
+/// Other formats, and images without any metadata to keep, are encoded as they are.
+pub fn encode(
+    img: &DynamicImage,
+    fmt: ImageFormat,
+    metadata: &Metadata,
+    keep: &KeepMetadata,
+) -> Result<Vec<u8>, String> {
+    let size = Size::from(img.dimensions());
+    let exif = metadata.exif(&size, keep);
+    let xmp = metadata.xmp(&size, keep);
+    let icc = metadata.icc(keep);
+
+    if exif.is_empty() && xmp.is_none() && icc.is_none() {
+        return plain(img, fmt);
+    }
+    let xmp = xmp.as_deref();
+    match fmt {
+        ImageFormat::Jpeg => jpeg(img, &exif_block(&exif, metadata.little_endian())?, xmp, icc),
+        ImageFormat::Png => png(img, &exif_block(&exif, metadata.little_endian())?, xmp, icc),
+        ImageFormat::WebP => webp(img, &exif_block(&exif, metadata.little_endian())?, xmp, icc),
+        ImageFormat::Tiff => tiff(img, exif, metadata.little_endian(), xmp, icc),
+        _ => {
+            log::debug!("Metadata cannot be saved in {fmt:?} files and was left out.");
+            plain(img, fmt)
+        }
+    }
+}
+
+/// Encode the image without any metadata.
+fn plain(img: &DynamicImage, fmt: ImageFormat) -> Result<Vec<u8>, String> {
+    let mut bytes = Cursor::new(Vec::new());
+    img.write_to(&mut bytes, fmt).map_err(|e| e.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+/// Write the EXIF fields as a TIFF structure, as stored in JPEG, PNG and WebP files.
+fn exif_block(fields: &[Field], little_endian: bool) -> Result<Option<Vec<u8>>, String> {
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    let mut writer = Writer::new();
+    fields.iter().for_each(|field| writer.push_field(field));
+    let mut block = Cursor::new(Vec::new());
+    writer
+        .write(&mut block, little_endian)
+        .map_err(|e| e.to_string())?;
+    Ok(Some(block.into_inner()))
+}
+
+/// Encode a JPEG file, with the EXIF data and XMP packet in APP1 segments
+/// right after the JFIF header.
+fn jpeg(
+    img: &DynamicImage,
+    exif: &Option<Vec<u8>>,
+    xmp: Option<&[u8]>,
+    icc: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut encoder = JpegEncoder::new(&mut bytes);
+    if let Some(icc) = icc {
+        encoder
+            .set_icc_profile(icc.to_vec())
+            .map_err(|e| e.to_string())?;
+    }
+    img.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+    let mut jpeg = Jpeg::from_bytes(Bytes::from(bytes)).map_err(|e| e.to_string())?;
+
+    let segments = [(EXIF_JPEG_PREFIX, exif.as_deref()), (XMP_JPEG_PREFIX, xmp)]
+        .into_iter()
+        .filter_map(|(prefix, data)| {
+            data.map(|data| {
+                JpegSegment::new_with_contents(markers::APP1, Bytes::from([prefix, data].concat()))
+            })
+        });
+    let at = usize::from(
+        jpeg.segments()
+            .first()
+            .is_some_and(|segment| segment.marker() == markers::APP0),
+    );
+    jpeg.segments_mut().splice(at..at, segments);
+
+    Ok(jpeg.encoder().bytes().to_vec())
+}
+
+/// Encode a PNG file, with the EXIF data in an eXIf chunk and the XMP packet in an iTXt chunk,
+/// both ahead of the image data.
+fn png(
+    img: &DynamicImage,
+    exif: &Option<Vec<u8>>,
+    xmp: Option<&[u8]>,
+    icc: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut encoder = PngEncoder::new(&mut bytes);
+    if let Some(icc) = icc {
+        encoder
+            .set_icc_profile(icc.to_vec())
+            .map_err(|e| e.to_string())?;
+    }
+    img.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+    let mut png = Png::from_bytes(Bytes::from(bytes)).map_err(|e| e.to_string())?;
+
+    let exif = exif
+        .as_deref()
+        .map(|exif| PngChunk::new(*b"eXIf", Bytes::copy_from_slice(exif)));
+    // The keyword, followed by no compression, and an empty language tag and translated keyword.
+    let xmp = xmp.map(|xmp| {
+        PngChunk::new(
+            *b"iTXt",
+            Bytes::from([XMP_PNG_KEYWORD, b"\0\0\0\0\0", xmp].concat()),
+        )
+    });
+    let at = png
+        .chunks()
+        .iter()
+        .position(|chunk| chunk.kind() == *b"IDAT")
+        .unwrap_or(png.chunks().len());
+    png.chunks_mut().splice(at..at, exif.into_iter().chain(xmp));
+
+    Ok(png.encoder().bytes().to_vec())
+}
+
+/// Encode a lossless WebP file, with the metadata in its own chunks.
+fn webp(
+    img: &DynamicImage,
+    exif: &Option<Vec<u8>>,
+    xmp: Option<&[u8]>,
+    icc: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let pixels = img.to_rgba8();
+    let mut bytes = Vec::new();
+    let mut encoder = image_webp::WebPEncoder::new(&mut bytes);
+    if let Some(exif) = exif {
+        encoder.set_exif_metadata(exif.clone());
+    }
+    if let Some(xmp) = xmp {
+        encoder.set_xmp_metadata(xmp.to_vec());
+    }
+    if let Some(icc) = icc {
+        encoder.set_icc_profile(icc.to_vec());
+    }
+    encoder
+        .encode(
+            pixels.as_raw(),
+            pixels.width(),
+            pixels.height(),
+            image_webp::ColorType::Rgba8,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Encode an uncompressed TIFF file, with the metadata alongside the tags describing the pixels.
+fn tiff(
+    img: &DynamicImage,
+    exif: Vec<Field>,
+    little_endian: bool,
+    xmp: Option<&[u8]>,
+    icc: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let pixels = img.to_rgba8();
+    let (width, height) = pixels.dimensions();
+    let field = |tag, value| Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value,
+    };
+
+    let mut fields: Vec<Field> = exif
+        .into_iter()
+        .filter(|field| !matches!(field.tag, Tag::ImageWidth | Tag::ImageLength))
+        .collect();
+    fields.extend([
+        field(Tag::ImageWidth, Value::Long(vec![width])),
+        field(Tag::ImageLength, Value::Long(vec![height])),
+        field(Tag::BitsPerSample, Value::Short(vec![8; 4])),
+        field(Tag::Compression, Value::Short(vec![1])),
+        field(Tag::PhotometricInterpretation, Value::Short(vec![2])),
+        field(Tag::SamplesPerPixel, Value::Short(vec![4])),
+        field(Tag::RowsPerStrip, Value::Long(vec![height])),
+        field(Tag::PlanarConfiguration, Value::Short(vec![1])),
+        // The fourth sample is unassociated alpha.
+        field(EXTRA_SAMPLES_TAG, Value::Short(vec![2])),
+    ]);
+    if let Some(xmp) = xmp {
+        fields.push(field(XMP_TAG, Value::Byte(xmp.to_vec())));
+    }
+    if let Some(icc) = icc {
+        fields.push(field(ICC_TAG, Value::Undefined(icc.to_vec(), 0)));
+    }
+
+    let strips = [pixels.as_raw().as_slice()];
+    let mut writer = Writer::new();
+    fields.iter().for_each(|field| writer.push_field(field));
+    writer.set_strips(&strips, In::PRIMARY);
+    let mut bytes = Cursor::new(Vec::new());
+    writer
+        .write(&mut bytes, little_endian)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TEST_FS_PREFIX, save_jpeg_with_orientation};
+    use exif::Reader;
+    use image::{ImageDecoder, ImageReader, RgbImage, RgbaImage};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::Path;
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    /// Encode a 30×20 image with the metadata of a fixture and a stand-in ICC profile,
+    /// and save it to a file with the given extension.
+    fn encode_to(dir: &TempDir, fmt: ImageFormat, keep: &str) -> std::path::PathBuf {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(30, 20));
+        let img = match fmt {
+            ImageFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8()),
+            _ => img,
+        };
+        let metadata = Metadata::read(Path::new("images/test/test_01.jpg"))
+            .with_icc_profile(Some(b"icc profile".to_vec()));
+        let keep = KeepMetadata::from_str(keep).unwrap();
+        let bytes = encode(&img, fmt, &metadata, &keep).unwrap();
+        let path = dir.path().join(format!("out.{}", fmt.extensions_str()[0]));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn metadata_is_carried_over() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        for fmt in [
+            ImageFormat::Jpeg,
+            ImageFormat::Png,
+            ImageFormat::WebP,
+            ImageFormat::Tiff,
+        ] {
+            let path = encode_to(&temp_dir, fmt, "all");
+
+            let mut decoder = ImageReader::open(&path).unwrap().into_decoder().unwrap();
+            assert_eq!(decoder.dimensions(), (30, 20), "{fmt:?}");
+            assert_eq!(
+                decoder.icc_profile().unwrap().as_deref(),
+                Some(&b"icc profile"[..]),
+                "{fmt:?}"
+            );
+
+            let exif = Reader::new()
+                .read_from_container(&mut BufReader::new(File::open(&path).unwrap()))
+                .unwrap();
+            let value = |tag| exif.get_field(tag, In::PRIMARY).unwrap().value.get_uint(0);
+            assert_eq!(value(Tag::PixelXDimension), Some(30), "{fmt:?}");
+            assert_eq!(value(Tag::PixelYDimension), Some(20), "{fmt:?}");
+
+            let metadata = Metadata::read(&path);
+            assert_eq!(metadata.get("model"), Some("X100V"), "{fmt:?}");
+            assert!(
+                metadata
+                    .xmp(&Size::new(30, 20), &KeepMetadata::default())
+                    .is_some(),
+                "{fmt:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn orientation_is_reset() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("rotated.jpg");
+        save_jpeg_with_orientation(&input, &RgbImage::new(20, 30), 6);
+        let metadata = Metadata::read(&input);
+        let img = DynamicImage::ImageRgb8(RgbImage::new(30, 20));
+
+        for fmt in [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::Tiff] {
+            let bytes = encode(&img, fmt, &metadata, &KeepMetadata::default()).unwrap();
+            let exif = Reader::new()
+                .read_from_container(&mut Cursor::new(bytes))
+                .unwrap();
+            let orientation = exif.get_field(Tag::Orientation, In::PRIMARY).unwrap();
+            assert_eq!(orientation.value.get_uint(0), Some(1), "{fmt:?}");
+        }
+    }
+
+    #[test]
+    fn metadata_can_be_left_out() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        for fmt in [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::Tiff] {
+            let path = encode_to(&temp_dir, fmt, "none");
+            let metadata = Metadata::read(&path);
+            assert_eq!(metadata.get("model"), None, "{fmt:?}");
+            assert!(
+                metadata
+                    .xmp(&Size::new(30, 20), &KeepMetadata::default())
+                    .is_none(),
+                "{fmt:?}"
+            );
+
+            let path = encode_to(&temp_dir, fmt, "icc");
+            let metadata = Metadata::read(&path);
+            assert_eq!(metadata.get("model"), None, "{fmt:?}");
+            let mut decoder = ImageReader::open(&path).unwrap().into_decoder().unwrap();
+            assert!(decoder.icc_profile().unwrap().is_some(), "{fmt:?}");
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! The fill module is responsible for parsing and sampling the paint used for the frame.
+use crate::errors::OliframeError;
+use crate::frame::Blending;
+use crate::geometry::{HorizontalPosition, RelativePosition, Size, VerticalPosition};
+use csscolorparser::Color;
+use image::Rgba;
+use std::str::FromStr;
+
+/// The paint used to fill the frame around the image.
+///
+/// The user can provide the fill in the following formats:
+/// - Any valid CSS color: `white`, `#333`, `rgb(10, 20, 30)`
+/// - A CSS linear gradient: `linear-gradient(45deg, #fff, #ccc)`, `linear-gradient(to right, red, blue 80%)`
+/// - A CSS radial gradient: `radial-gradient(#fff, #ccc)`, `radial-gradient(circle at top left, red, blue)`
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fill {
+    /// A single flat color.
+    Solid(Rgba<u8>),
+    /// A gradient along a straight line across the frame.
+    Linear {
+        /// The direction of the gradient line.
+        direction: LinearDirection,
+        /// The color stops along the gradient line.
+        stops: Vec<ColorStop>,
+    },
+    /// A gradient radiating outward from a point in the frame.
+    Radial {
+        /// The shape of the gradient's ending shape.
+        shape: RadialShape,
+        /// The center of the gradient.
+        center: RelativePosition,
+        /// The color stops along the gradient ray.
+        stops: Vec<ColorStop>,
+    },
+}
+
+/// The direction of a linear gradient.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinearDirection {
+    /// A CSS angle, in degrees, where 0 points up and 90 points right.
+    Angle(f32),
+    /// Toward a corner of the frame, given as horizontal and vertical signs (-1 or 1).
+    Corner(f32, f32),
+}
+
+/// The ending shape of a radial gradient.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum RadialShape {
+    /// A circle reaching the farthest corner of the frame.
+    Circle,
+    /// An ellipse reaching the farthest corner of the frame.
+    #[default]
+    Ellipse,
+}
+
+/// A single color stop in a gradient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    /// The color at this stop.
+    color: Rgba<u8>,
+    /// The position of the stop along the gradient, from 0 to 1.
+    offset: f32,
+}
+
+impl Fill {
+    /// Sample the fill at the given pixel of a frame with the given size,
+    /// interpolating gradients in the given blending space.
+    pub fn sample(&self, x: u32, y: u32, size: &Size, blending: Blending) -> Rgba<u8> {
+        let (width, height) = (size.width() as f32, size.height() as f32);
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        match self {
+            Fill::Solid(color) => *color,
+            Fill::Linear { direction, stops } => {
+                let (dx, dy) = match direction {
+                    LinearDirection::Angle(degrees) => {
+                        let radians = degrees.to_radians();
+                        (radians.sin(), -radians.cos())
+                    }
+                    LinearDirection::Corner(sx, sy) => {
+                        let norm = (width * width + height * height).sqrt().max(f32::EPSILON);
+                        (sx * height / norm, sy * width / norm)
+                    }
+                };
+                let length = (width * dx).abs() + (height * dy).abs();
+                let t = ((px - width / 2.) * dx + (py - height / 2.) * dy)
+                    / length.max(f32::EPSILON)
+                    + 0.5;
+                interpolate(stops, t, blending)
+            }
+            Fill::Radial {
+                shape,
+                center,
+                stops,
+            } => {
+                let cx = match center.horizontal() {
+                    HorizontalPosition::Left => 0.,
+                    HorizontalPosition::Center => width / 2.,
+                    HorizontalPosition::Right => width,
+                };
+                let cy = match center.vertical() {
+                    VerticalPosition::Top => 0.,
+                    VerticalPosition::Center => height / 2.,
+                    VerticalPosition::Bottom => height,
+                };
+                let far_x = cx.max(width - cx).max(f32::EPSILON);
+                let far_y = cy.max(height - cy).max(f32::EPSILON);
+                let t = match shape {
+                    RadialShape::Circle => {
+                        ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+                            / (far_x * far_x + far_y * far_y).sqrt()
+                    }
+                    RadialShape::Ellipse => {
+                        (((px - cx) / far_x).powi(2) + ((py - cy) / far_y).powi(2)).sqrt()
+                            / std::f32::consts::SQRT_2
+                    }
+                };
+                interpolate(stops, t, blending)
+            }
+        }
+    }
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Fill::Solid(Rgba([255, 255, 255, 255]))
+    }
+}
+
+impl FromStr for Fill {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if let Some(args) = function_args(input, "linear-gradient") {
+            parse_linear(&args)
+        } else if let Some(args) = function_args(input, "radial-gradient") {
+            parse_radial(&args)
+        } else {
+            parse_color(input).map(Fill::Solid)
+        }
+    }
+}
+
+/// Find the color at the given position along the gradient.
+fn interpolate(stops: &[ColorStop], t: f32, blending: Blending) -> Rgba<u8> {
+    let first = &stops[0];
+    let last = &stops[stops.len() - 1];
+    if t <= first.offset {
+        return first.color;
+    }
+    if t >= last.offset {
+        return last.color;
+    }
+
+    stops
+        .windows(2)
+        .find(|pair| t <= pair[1].offset)
+        .map(|pair| {
+            let span = pair[1].offset - pair[0].offset;
+            if span <= f32::EPSILON {
+                return pair[1].color;
+            }
+            let weight = (t - pair[0].offset) / span;
+            blending.interpolate(&pair[0].color, &pair[1].color, weight)
+        })
+        .unwrap_or(last.color)
+}
+
+/// Extract the comma-separated arguments of a CSS function call, if the input is that function.
+fn function_args(input: &str, name: &str) -> Option<Vec<String>> {
+    let lowercase = input.to_lowercase();
+    let inner = lowercase
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    args.push(current.trim().to_string());
+    Some(args)
+}
+
+/// Parse the arguments of a `linear-gradient()`.
+fn parse_linear(args: &[String]) -> Result<Fill, OliframeError> {
+    let (direction, stops) = match parse_direction(&args[0])? {
+        Some(direction) => (direction, &args[1..]),
+        None => (LinearDirection::Angle(180.), args),
+    };
+
+    Ok(Fill::Linear {
+        direction,
+        stops: parse_stops(stops)?,
+    })
+}
+
+/// Parse the arguments of a `radial-gradient()`.
+fn parse_radial(args: &[String]) -> Result<Fill, OliframeError> {
+    let first = &args[0];
+    let (shape_str, position_str) = match first.split_once("at ") {
+        Some((shape, position)) => (shape.trim(), Some(position.trim())),
+        None => (first.as_str(), None),
+    };
+
+    let shape = match shape_str {
+        "circle" => Some(RadialShape::Circle),
+        "ellipse" => Some(RadialShape::Ellipse),
+        "" if position_str.is_some() => Some(RadialShape::default()),
+        _ => None,
+    };
+
+    match shape {
+        Some(shape) => {
+            let center = position_str
+                .map(RelativePosition::from_str)
+                .transpose()?
+                .unwrap_or_default();
+            Ok(Fill::Radial {
+                shape,
+                center,
+                stops: parse_stops(&args[1..])?,
+            })
+        }
+        None => Ok(Fill::Radial {
+            shape: RadialShape::default(),
+            center: RelativePosition::default(),
+            stops: parse_stops(args)?,
+        }),
+    }
+}
+
+/// Parse the direction of a linear gradient, if the argument is one.
+fn parse_direction(arg: &str) -> Result<Option<LinearDirection>, OliframeError> {
+    if let Some(sides) = arg.strip_prefix("to ") {
+        let mut sx = 0.;
+        let mut sy = 0.;
+        for side in sides.split_whitespace() {
+            match side {
+                "left" => sx = -1.,
+                "right" => sx = 1.,
+                "top" => sy = -1.,
+                "bottom" => sy = 1.,
+                _ => {
+                    return Err(OliframeError::InvalidInput(format!(
+                        "Invalid gradient direction: {arg}"
+                    )));
+                }
+            }
+        }
+        return Ok(Some(match (sx, sy) {
+            (0., 0.) => {
+                return Err(OliframeError::InvalidInput(format!(
+                    "Invalid gradient direction: {arg}"
+                )));
+            }
+            (sx, 0.) => LinearDirection::Angle(90. * sx),
+            (0., sy) => LinearDirection::Angle(90. + 90. * sy),
+            (sx, sy) => LinearDirection::Corner(sx, sy),
+        }));
+    }
+
+    let units = [
+        ("deg", 1.),
+        ("grad", 0.9),
+        ("rad", 180. / std::f32::consts::PI),
+        ("turn", 360.),
+    ];
+    for (unit, factor) in units {
+        if let Some(value) = arg.strip_suffix(unit) {
+            if let Ok(value) = value.trim().parse::<f32>() {
+                return Ok(Some(LinearDirection::Angle(value * factor)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parse a list of color stops, filling in missing offsets as CSS does.
+fn parse_stops(args: &[String]) -> Result<Vec<ColorStop>, OliframeError> {
+    if args.len() < 2 {
+        return Err(OliframeError::InvalidInput(
+            "Gradients need at least two color stops.".to_string(),
+        ));
+    }
+
+    let parsed = args
+        .iter()
+        .map(|arg| parse_stop(arg))
+        .collect::<Result<Vec<_>, OliframeError>>()?;
+
+    let mut offsets: Vec<Option<f32>> = parsed.iter().map(|(_, offset)| *offset).collect();
+    let last = offsets.len() - 1;
+    offsets[0] = offsets[0].or(Some(0.));
+    offsets[last] = offsets[last].or(Some(1.));
+
+    // Offsets may never move backward along the gradient.
+    let mut max = 0f32;
+    for offset in offsets.iter_mut().flatten() {
+        max = max.max(*offset);
+        *offset = max;
+    }
+
+    // Evenly distribute any stops without offsets between their neighbors.
+    let mut start = 0;
+    for end in 1..offsets.len() {
+        if let Some(end_offset) = offsets[end] {
+            let start_offset = offsets[start].expect("Start offset is always resolved.");
+            let span = (end - start) as f32;
+            for (i, offset) in offsets.iter_mut().enumerate().take(end).skip(start + 1) {
+                *offset =
+                    Some(start_offset + (end_offset - start_offset) * (i - start) as f32 / span);
+            }
+            start = end;
+        }
+    }
+
+    Ok(parsed
+        .into_iter()
+        .zip(offsets)
+        .map(|((color, _), offset)| ColorStop {
+            color,
+            offset: offset.expect("All offsets are resolved."),
+        })
+        .collect())
+}
+
+/// Parse a single color stop, with an optional percentage offset.
+fn parse_stop(arg: &str) -> Result<(Rgba<u8>, Option<f32>), OliframeError> {
+    if let Some((color, offset)) = arg.rsplit_once(' ') {
+        if let Some(percent) = offset.strip_suffix('%') {
+            let percent = percent
+                .parse::<f32>()
+                .map_err(|_| OliframeError::InvalidInput(format!("Invalid color stop: {arg}")))?;
+            return Ok((parse_color(color.trim())?, Some(percent / 100.)));
+        }
+    }
+    Ok((parse_color(arg)?, None))
+}
+
+/// Parse a single CSS color.
+fn parse_color(input: &str) -> Result<Rgba<u8>, OliframeError> {
+    input
+        .parse::<Color>()
+        .map(|c| Rgba(c.to_rgba8()))
+        .map_err(|_| OliframeError::InvalidInput("Invalid color.".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::composite::linear_to_srgb;
+
+    #[test]
+    fn parse_solid_color() {
+        let fill = "black".parse::<Fill>().unwrap();
+        assert_eq!(fill, Fill::Solid(Rgba([0, 0, 0, 255])));
+
+        let fill = "rgb(10, 20, 30)".parse::<Fill>().unwrap();
+        assert_eq!(fill, Fill::Solid(Rgba([10, 20, 30, 255])));
+    }
+
+    #[test]
+    fn invalid_color_is_err() {
+        let fill = "not-a-color".parse::<Fill>();
+        assert_eq!(
+            fill.unwrap_err().to_string(),
+            "Invalid input: Invalid color."
+        );
+    }
+
+    #[test]
+    fn parse_linear_gradient() {
+        let fill = "linear-gradient(45deg, #fff, rgb(0, 0, 0) 80%)"
+            .parse::<Fill>()
+            .unwrap();
+        assert_eq!(
+            fill,
+            Fill::Linear {
+                direction: LinearDirection::Angle(45.),
+                stops: vec![
+                    ColorStop {
+                        color: Rgba([255, 255, 255, 255]),
+                        offset: 0.
+                    },
+                    ColorStop {
+                        color: Rgba([0, 0, 0, 255]),
+                        offset: 0.8
+                    },
+                ],
+            }
+        );
+
+        let fill = "linear-gradient(to right, red, blue)"
+            .parse::<Fill>()
+            .unwrap();
+        assert!(matches!(
+            fill,
+            Fill::Linear {
+                direction: LinearDirection::Angle(90.),
+                ..
+            }
+        ));
+
+        let fill = "linear-gradient(0.25turn, red, blue)"
+            .parse::<Fill>()
+            .unwrap();
+        assert!(matches!(
+            fill,
+            Fill::Linear {
+                direction: LinearDirection::Angle(90.),
+                ..
+            }
+        ));
+
+        let fill = "linear-gradient(red, blue)".parse::<Fill>().unwrap();
+        assert!(matches!(
+            fill,
+            Fill::Linear {
+                direction: LinearDirection::Angle(180.),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_stop_offsets_are_distributed() {
+        let fill = "linear-gradient(red, white, blue, black)"
+            .parse::<Fill>()
+            .unwrap();
+        let Fill::Linear { stops, .. } = fill else {
+            panic!("Expected a linear gradient");
+        };
+        let offsets: Vec<f32> = stops.iter().map(|s| s.offset).collect();
+        assert_eq!(offsets, vec![0., 1. / 3., 2. / 3., 1.]);
+    }
+
+    #[test]
+    fn parse_radial_gradient() {
+        let fill = "radial-gradient(circle at top left, red, blue)"
+            .parse::<Fill>()
+            .unwrap();
+        let Fill::Radial { shape, center, .. } = fill else {
+            panic!("Expected a radial gradient");
+        };
+        assert_eq!(shape, RadialShape::Circle);
+        assert_eq!(center.horizontal(), &HorizontalPosition::Left);
+        assert_eq!(center.vertical(), &VerticalPosition::Top);
+
+        let fill = "radial-gradient(red, blue)".parse::<Fill>().unwrap();
+        assert!(matches!(
+            fill,
+            Fill::Radial {
+                shape: RadialShape::Ellipse,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn invalid_gradients_are_err() {
+        assert!("linear-gradient(red)".parse::<Fill>().is_err());
+        assert!(
+            "linear-gradient(to nowhere, red, blue)"
+                .parse::<Fill>()
+                .is_err()
+        );
+        assert!("radial-gradient(red, not-a-color)".parse::<Fill>().is_err());
+    }
+
+    #[test]
+    fn sample_linear_gradient() {
+        let fill = "linear-gradient(to right, black, white)"
+            .parse::<Fill>()
+            .unwrap();
+        let size = Size::new(100, 10);
+        assert_eq!(
+            fill.sample(0, 5, &size, Blending::Srgb),
+            Rgba([1, 1, 1, 255])
+        );
+        assert_eq!(
+            fill.sample(50, 5, &size, Blending::Srgb),
+            Rgba([129, 129, 129, 255])
+        );
+        assert_eq!(
+            fill.sample(99, 5, &size, Blending::Srgb),
+            Rgba([254, 254, 254, 255])
+        );
+    }
+
+    #[test]
+    fn sample_linear_gradient_in_linear_light() {
+        let fill = "linear-gradient(to right, black, white)"
+            .parse::<Fill>()
+            .unwrap();
+        let size = Size::new(100, 10);
+        // Halfway along the gradient, the light is halfway between black and white.
+        let weight = 50.5 / 100.;
+        let expected = (linear_to_srgb(weight) * 255.).round() as u8;
+        assert_eq!(
+            fill.sample(50, 5, &size, Blending::Linear),
+            Rgba([expected, expected, expected, 255])
+        );
+    }
+
+    #[test]
+    fn sample_corner_gradient() {
+        let fill = "linear-gradient(to bottom right, black, white)"
+            .parse::<Fill>()
+            .unwrap();
+        let size = Size::new(200, 100);
+        // The top-right and bottom-left corners lie on the gradient's midline.
+        let top_right = fill.sample(199, 0, &size, Blending::Srgb)[0] as i32;
+        let bottom_left = fill.sample(0, 99, &size, Blending::Srgb)[0] as i32;
+        assert!((top_right - 127).abs() <= 1);
+        assert!((bottom_left - 127).abs() <= 1);
+        assert!(fill.sample(0, 0, &size, Blending::Srgb)[0] < 5);
+        assert!(fill.sample(199, 99, &size, Blending::Srgb)[0] > 250);
+    }
+
+    #[test]
+    fn sample_radial_gradient() {
+        let fill = "radial-gradient(black, white)".parse::<Fill>().unwrap();
+        let size = Size::new(100, 100);
+        assert!(fill.sample(49, 49, &size, Blending::Srgb)[0] < 5);
+        assert!(fill.sample(0, 0, &size, Blending::Srgb)[0] > 250);
+        assert_eq!(
+            fill.sample(0, 50, &size, Blending::Srgb),
+            fill.sample(50, 0, &size, Blending::Srgb)
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! The keyline module is responsible for drawing a thin stroke around the image, inside the frame.
+use crate::frame::composite::{self, Blending};
+use crate::frame::outline::Outline;
+use crate::geometry::{Border, Length, Size};
+use derive_getters::Getters;
+use image::{Rgba, RgbaImage};
+
+/// A thin stroke drawn around the image, like the keyline of a gallery mat.
+///
+/// The width and gap are resolved against (width + height) / 2 of the image when relative.
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct Keyline {
+    /// The width of the stroke.
+    width: Length,
+    /// The space between the image and the stroke.
+    gap: Length,
+    /// The color of the stroke.
+    color: Rgba<u8>,
+}
+
+impl Keyline {
+    /// Create a new keyline.
+    pub fn new(width: Length, gap: Length, color: Rgba<u8>) -> Self {
+        Self { width, gap, color }
+    }
+
+    /// The space needed on each side of the image to draw the keyline.
+    pub fn extent(&self, img_size: &Size) -> Border {
+        let (width, gap) = self.resolve(img_size);
+        let side = width.saturating_add(gap);
+        Border::new(side, side, side, side)
+    }
+
+    /// Paint the keyline around the image window onto the canvas.
+    pub fn paint(&self, canvas: &mut RgbaImage, window: &Outline, blending: Blending) {
+        let (width, gap) = self.resolve(window.size());
+        if width == 0 {
+            return;
+        }
+
+        let inner = window.around(&Border::new(gap, gap, gap, gap));
+        let outer = window.around(&self.extent(window.size()));
+        let (left, top, right, bottom) = outer.bounds(canvas.width(), canvas.height());
+
+        for y in top..bottom {
+            for x in left..right {
+                let coverage = outer.coverage(x, y) - inner.coverage(x, y);
+                if coverage > 0. {
+                    composite::over(canvas.get_pixel_mut(x, y), &self.color, coverage, blending);
+                }
+            }
+        }
+    }
+
+    /// Resolve the width and gap of the keyline to pixels for an image of the given size.
+    fn resolve(&self, img_size: &Size) -> (u32, u32) {
+        let basis = (img_size.width() + img_size.height()) as f32 / 2.;
+        (self.width.to_pixels(basis), self.gap.to_pixels(basis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Point, Radii};
+    use crate::test_utils::window;
+
+    #[test]
+    fn keyline_extent() {
+        let keyline = Keyline::new(Length::Pixels(2.), Length::Relative(0.01), Rgba([0; 4]));
+        let extent = keyline.extent(&Size::new(300, 100));
+        assert_eq!(extent.top(), 4);
+        assert_eq!(extent.left(), 4);
+    }
+
+    #[test]
+    fn paint_keyline_with_gap() {
+        let keyline = Keyline::new(Length::Pixels(2.), Length::Pixels(1.), Rgba([0, 0, 0, 255]));
+        let mut canvas = RgbaImage::from_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        keyline.paint(
+            &mut canvas,
+            &window(Point::new(5, 5), Size::new(10, 10), Radii::default()),
+            Blending::Srgb,
+        );
+
+        assert_eq!(canvas.get_pixel(1, 10), &Rgba([255, 255, 255, 255]));
+        assert_eq!(canvas.get_pixel(2, 10), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(3, 10), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(4, 10), &Rgba([255, 255, 255, 255]));
+        assert_eq!(canvas.get_pixel(10, 10), &Rgba([255, 255, 255, 255]));
+        assert_eq!(canvas.get_pixel(16, 16), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn keyline_follows_corner_radius() {
+        let keyline = Keyline::new(Length::Pixels(2.), Length::Pixels(0.), Rgba([0, 0, 0, 255]));
+        let mut canvas = RgbaImage::from_pixel(40, 40, Rgba([255, 255, 255, 255]));
+        keyline.paint(
+            &mut canvas,
+            &window(Point::new(10, 10), Size::new(20, 20), Radii::uniform(8)),
+            Blending::Srgb,
+        );
+
+        // The square corner of the outline is left untouched...
+        assert_eq!(canvas.get_pixel(8, 8), &Rgba([255, 255, 255, 255]));
+        // ...while the straight edges are fully painted.
+        assert_eq!(canvas.get_pixel(8, 20), &Rgba([0, 0, 0, 255]));
+        // Pixels along the curve are anti-aliased.
+        assert!(canvas.pixels().any(|p| p[0] > 0 && p[0] < 255));
+    }
+}
@@ -0,0 +1,286 @@
+//! The mask module is responsible for cutting the image window into shapes other than a rectangle.
+use crate::errors::OliframeError;
+use crate::frame::{Antialias, build};
+use crate::geometry::Size;
+use image::{GrayImage, Luma, imageops};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The most sides a polygon can have, as each side adds to the cost of covering every pixel.
+const MAX_POLYGON_SIDES: u32 = 64;
+
+/// The shape of the window through which the image is seen, fitted to the image's rectangle.
+///
+/// The user can provide the shape in the following formats:
+/// - `circle`: the largest circle centered in the image
+/// - `ellipse`: the ellipse filling the image
+/// - `polygon:6`: the largest regular polygon with the given number of sides, resting on a flat edge
+///
+/// Alternatively, the alpha channel of an image file can be used as the mask.
+///
+#[derive(Clone, Default, PartialEq)]
+pub enum Mask {
+    /// The image's rectangle, rounded by the corner radius.
+    #[default]
+    Rectangle,
+    /// The largest circle centered in the image.
+    Circle,
+    /// The ellipse filling the image.
+    Ellipse,
+    /// The largest regular polygon with the given number of sides centered in the image.
+    Polygon(u32),
+    /// The alpha channel of a mask image, stretched to the image.
+    Alpha(Arc<GrayImage>),
+}
+
+impl Mask {
+    /// Load a mask from the alpha channel of the given image file.
+    pub fn load(path: &PathBuf) -> Result<Self, OliframeError> {
+        let (image, _, _) = build::load(path)?;
+        let image = image.to_rgba8();
+        Ok(Mask::Alpha(Arc::new(GrayImage::from_fn(
+            image.width(),
+            image.height(),
+            |x, y| Luma([image.get_pixel(x, y)[3]]),
+        ))))
+    }
+
+    /// The fraction (0 to 1) of the pixel at `(x, y)`, relative to the top-left corner
+    /// of a rectangle of the given size, covered by the mask fitted to that rectangle.
+    ///
+    /// Returns `None` for the rectangle, which is shaped by the corner radii instead.
+    pub fn coverage(&self, x: u32, y: u32, size: &Size, antialias: Antialias) -> Option<f32> {
+        let (width, height) = (size.width() as f32, size.height() as f32);
+        let (x, y) = (x as f32, y as f32);
+        let coverage = match self {
+            Mask::Rectangle => return None,
+            Mask::Alpha(alpha) => match antialias {
+                Antialias::Analytic => sample(alpha, (x + 0.5) / width, (y + 0.5) / height),
+                Antialias::Supersample(samples) => {
+                    let total: f32 = offsets(samples)
+                        .flat_map(|dx| offsets(samples).map(move |dy| (dx, dy)))
+                        .map(|(dx, dy)| sample(alpha, (x + dx) / width, (y + dy) / height))
+                        .sum();
+                    total / (samples * samples) as f32
+                }
+            },
+            _ => {
+                // Distances are measured from the center of the rectangle.
+                let (cx, cy) = (width / 2., height / 2.);
+                match antialias {
+                    // The boundary is treated as a straight line across the pixel.
+                    Antialias::Analytic => {
+                        0.5 - self.distance(x + 0.5 - cx, y + 0.5 - cy, width, height)
+                    }
+                    Antialias::Supersample(samples) => {
+                        let inside = offsets(samples)
+                            .flat_map(|dx| offsets(samples).map(move |dy| (dx, dy)))
+                            .filter(|(dx, dy)| {
+                                self.distance(x + dx - cx, y + dy - cy, width, height) <= 0.
+                            })
+                            .count();
+                        inside as f32 / (samples * samples) as f32
+                    }
+                }
+            }
+        };
+        Some(coverage.clamp(0., 1.))
+    }
+
+    /// The signed distance, in pixels, from a point to the edge of the shape
+    /// fitted to a rectangle of the given size (negative inside the shape).
+    ///
+    /// The point is given relative to the center of the rectangle.
+    fn distance(&self, x: f32, y: f32, width: f32, height: f32) -> f32 {
+        let radius = width.min(height) / 2.;
+        match self {
+            Mask::Circle => x.hypot(y) - radius,
+            Mask::Ellipse => {
+                let (a, b) = (width / 2., height / 2.);
+                let f = (x / a).powi(2) + (y / b).powi(2) - 1.;
+                let gradient = (2. * x / (a * a)).hypot(2. * y / (b * b));
+                if gradient == 0. {
+                    -a.min(b)
+                } else {
+                    f / gradient
+                }
+            }
+            Mask::Polygon(sides) => {
+                // The first edge faces straight down, so the polygon rests on a flat edge.
+                let step = TAU / *sides as f32;
+                let apothem = radius * (PI / *sides as f32).cos();
+                (0..*sides)
+                    .map(|i| {
+                        let angle = FRAC_PI_2 + step * i as f32;
+                        x * angle.cos() + y * angle.sin()
+                    })
+                    .fold(f32::MIN, f32::max)
+                    - apothem
+            }
+            Mask::Rectangle | Mask::Alpha(_) => 0.,
+        }
+    }
+}
+
+/// The offsets of an N×N grid of samples within a pixel.
+fn offsets(samples: u32) -> impl Iterator<Item = f32> + Clone {
+    (0..samples).map(move |i| (i as f32 + 0.5) / samples as f32)
+}
+
+/// Sample the mask's alpha at the given relative coordinates (0 to 1).
+fn sample(alpha: &GrayImage, u: f32, v: f32) -> f32 {
+    imageops::sample_bilinear(alpha, u.clamp(0., 1.), v.clamp(0., 1.))
+        .map(|p| p[0] as f32 / 255.)
+        .unwrap_or(0.)
+}
+
+impl FromStr for Mask {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim().to_lowercase();
+        match input.split_once(':') {
+            None if input == "rectangle" => Ok(Mask::Rectangle),
+            None if input == "circle" => Ok(Mask::Circle),
+            None if input == "ellipse" => Ok(Mask::Ellipse),
+            Some(("polygon", sides)) => sides
+                .parse::<u32>()
+                .ok()
+                .filter(|sides| (3..=MAX_POLYGON_SIDES).contains(sides))
+                .map(Mask::Polygon)
+                .ok_or_else(|| {
+                    OliframeError::InvalidInput(format!(
+                        "Polygons must have 3-{MAX_POLYGON_SIDES} sides (received {sides})."
+                    ))
+                }),
+            _ => Err(OliframeError::InvalidInput(format!(
+                "Invalid mask shape: {input}"
+            ))),
+        }
+    }
+}
+
+impl Debug for Mask {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mask::Rectangle => write!(f, "Rectangle"),
+            Mask::Circle => write!(f, "Circle"),
+            Mask::Ellipse => write!(f, "Ellipse"),
+            Mask::Polygon(sides) => f.debug_tuple("Polygon").field(sides).finish(),
+            Mask::Alpha(alpha) => f
+                .debug_struct("Alpha")
+                .field("size", &alpha.dimensions())
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_masks() {
+        assert_eq!("circle".parse::<Mask>().unwrap(), Mask::Circle);
+        assert_eq!("Ellipse".parse::<Mask>().unwrap(), Mask::Ellipse);
+        assert_eq!("polygon:6".parse::<Mask>().unwrap(), Mask::Polygon(6));
+        assert!("polygon:2".parse::<Mask>().is_err());
+        assert_eq!("polygon:64".parse::<Mask>().unwrap(), Mask::Polygon(64));
+        assert!("polygon:1000000".parse::<Mask>().is_err());
+        assert!("polygon".parse::<Mask>().is_err());
+        assert!("star".parse::<Mask>().is_err());
+    }
+
+    #[test]
+    fn load_mask_from_file() {
+        let mask = Mask::load(&PathBuf::from("images/test/test_03.png")).unwrap();
+        assert!(matches!(mask, Mask::Alpha(_)));
+        assert!(Mask::load(&PathBuf::from("Cargo.toml")).is_err());
+    }
+
+    /// The total coverage of a mask fitted to a rectangle of the given size.
+    fn area(mask: &Mask, size: &Size, antialias: Antialias) -> f32 {
+        (0..size.width())
+            .flat_map(|x| (0..size.height()).map(move |y| (x, y)))
+            .map(|(x, y)| mask.coverage(x, y, size, antialias).unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn circle_coverage() {
+        let size = Size::new(100, 60);
+        let coverage = |x, y| Mask::Circle.coverage(x, y, &size, Antialias::default());
+        assert_eq!(coverage(50, 30), Some(1.));
+        assert_eq!(coverage(5, 30), Some(0.));
+        assert_eq!(coverage(80, 30), Some(0.));
+        assert_eq!(
+            Mask::Rectangle.coverage(0, 0, &size, Antialias::default()),
+            None
+        );
+
+        let expected = PI * 30. * 30.;
+        let area = area(&Mask::Circle, &size, Antialias::default());
+        assert!((area - expected).abs() < 1., "{area} != {expected}");
+    }
+
+    #[test]
+    fn ellipse_coverage() {
+        let size = Size::new(100, 60);
+        let coverage = |x, y| Mask::Ellipse.coverage(x, y, &size, Antialias::default());
+        assert_eq!(coverage(5, 30), Some(1.));
+        assert_eq!(coverage(2, 2), Some(0.));
+
+        let expected = PI * 50. * 30.;
+        let area = area(&Mask::Ellipse, &size, Antialias::default());
+        assert!((area - expected).abs() < 2., "{area} != {expected}");
+    }
+
+    #[test]
+    fn polygon_coverage() {
+        let size = Size::new(100, 100);
+        let coverage = |mask: &Mask, x, y| mask.coverage(x, y, &size, Antialias::default());
+        // A square rests on its bottom edge and fills the inscribed square.
+        let square = Mask::Polygon(4);
+        assert_eq!(coverage(&square, 50, 50), Some(1.));
+        assert_eq!(coverage(&square, 50, 5), Some(0.));
+        assert_eq!(coverage(&square, 50, 80), Some(1.));
+        // A triangle is flat at the bottom and pointed at the top.
+        let triangle = Mask::Polygon(3);
+        assert_eq!(coverage(&triangle, 50, 70), Some(1.));
+        assert_eq!(coverage(&triangle, 20, 10), Some(0.));
+
+        // A hexagon inscribed in a circle of radius 50.
+        let expected = 3. * 3_f32.sqrt() / 2. * 50. * 50.;
+        let area = area(&Mask::Polygon(6), &size, Antialias::default());
+        assert!((area - expected).abs() < 1., "{area} != {expected}");
+    }
+
+    #[test]
+    fn supersampled_coverage_matches_analytic_coverage() {
+        let size = Size::new(100, 100);
+        for mask in [Mask::Circle, Mask::Ellipse, Mask::Polygon(6)] {
+            for (x, y) in [(50, 0), (15, 15), (85, 50)] {
+                let analytic = mask.coverage(x, y, &size, Antialias::Analytic).unwrap();
+                let supersampled = mask
+                    .coverage(x, y, &size, Antialias::Supersample(16))
+                    .unwrap();
+                assert!(
+                    (analytic - supersampled).abs() < 0.1,
+                    "{mask:?} at ({x}, {y}): {analytic} != {supersampled}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn alpha_mask_coverage() {
+        let alpha = GrayImage::from_fn(2, 1, |x, _| Luma([if x == 0 { 0 } else { 255 }]));
+        let mask = Mask::Alpha(Arc::new(alpha));
+        let size = Size::new(100, 10);
+        assert_eq!(mask.coverage(5, 5, &size, Antialias::default()), Some(0.));
+        assert_eq!(mask.coverage(95, 5, &size, Antialias::default()), Some(1.));
+    }
+}
@@ -0,0 +1,137 @@
+//! The mat module is responsible for drawing nested borders between the image and the outer frame.
+use crate::errors::OliframeError;
+use crate::frame::composite;
+use crate::frame::outline::Outline;
+use crate::frame::{Blending, Fill};
+use crate::geometry::{Border, Margins, Size};
+use derive_getters::Getters;
+use image::RgbaImage;
+use std::str::FromStr;
+
+/// A mat is a border of its own color, nested between the image and the outer frame.
+///
+/// The user can provide a mat as its margins and its color, separated by a colon,
+/// where the margins follow the same rules as the frame margins: `2:black`, `3,2:gold`.
+///
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct Mat {
+    /// The relative margins of the mat around whatever it surrounds.
+    margins: Margins,
+    /// The color or gradient of the mat.
+    color: Fill,
+}
+
+impl FromStr for Mat {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (margins, color) = input.split_once(':').ok_or_else(|| {
+            OliframeError::InvalidInput(format!(
+                "Mats must be specified as SIZE(S):COLOR (received {input})."
+            ))
+        })?;
+
+        Ok(Self {
+            margins: Margins::from_str(margins)?,
+            color: Fill::from_str(color)?,
+        })
+    }
+}
+
+/// The total border taken up by a stack of mats,
+/// with relative margins measured against the given dimension.
+pub fn total_border(mats: &[Mat], dim: f32) -> Border {
+    mats.iter().fold(Border::default(), |border, mat| {
+        border + mat.margins.to_border(dim)
+    })
+}
+
+/// Paint a stack of mats, listed from the image outward, around the image window onto the canvas.
+pub fn paint_all(
+    mats: &[Mat],
+    canvas: &mut RgbaImage,
+    window: &Outline,
+    dim: f32,
+    blending: Blending,
+) {
+    let output_size = Size::from(canvas.dimensions());
+    let outlines: Vec<(&Mat, Outline)> = mats
+        .iter()
+        .scan(Border::default(), |distance, mat| {
+            *distance = distance.clone() + mat.margins.to_border(dim);
+            Some((mat, window.around(distance)))
+        })
+        .collect();
+
+    // Outer mats are painted first, so each inner mat covers the middle of the one around it.
+    for (mat, outline) in outlines.iter().rev() {
+        let (left, top, right, bottom) = outline.bounds(canvas.width(), canvas.height());
+        for y in top..bottom {
+            for x in left..right {
+                let coverage = outline.coverage(x, y);
+                if coverage > 0. {
+                    let color = mat.color.sample(x, y, &output_size, blending);
+                    composite::over(canvas.get_pixel_mut(x, y), &color, coverage, blending);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Length;
+    use crate::geometry::{Point, Radii};
+    use crate::test_utils::window;
+    use image::Rgba;
+
+    #[test]
+    fn parse_mat() {
+        let mat = "2:black".parse::<Mat>().unwrap();
+        assert_eq!(mat.margins().values(), &[Length::Relative(0.02)]);
+        assert_eq!(mat.color(), &Fill::Solid(Rgba([0, 0, 0, 255])));
+
+        let mat = "3,2:rgb(1, 2, 3)".parse::<Mat>().unwrap();
+        assert_eq!(
+            mat.margins().values(),
+            &[Length::Relative(0.03), Length::Relative(0.02)]
+        );
+        assert_eq!(mat.color(), &Fill::Solid(Rgba([1, 2, 3, 255])));
+    }
+
+    #[test]
+    fn invalid_mats_are_err() {
+        assert!("2".parse::<Mat>().is_err());
+        assert!("2:not-a-color".parse::<Mat>().is_err());
+        assert!(":black".parse::<Mat>().is_err());
+    }
+
+    #[test]
+    fn total_border_of_mats() {
+        let mats = vec![
+            "10:black".parse::<Mat>().unwrap(),
+            "5,20:gold".parse::<Mat>().unwrap(),
+        ];
+        let border = total_border(&mats, 100.);
+        assert_eq!(border, Border::new(15, 30, 15, 30));
+        assert_eq!(total_border(&[], 100.), Border::default());
+    }
+
+    #[test]
+    fn paint_nested_mats() {
+        let mats = vec![
+            "10:black".parse::<Mat>().unwrap(),
+            "10:red".parse::<Mat>().unwrap(),
+        ];
+        let mut canvas = RgbaImage::from_pixel(200, 200, Rgba([255, 255, 255, 255]));
+        let window = window(Point::new(50, 50), Size::new(100, 100), Radii::default());
+        paint_all(&mats, &mut canvas, &window, 100., Blending::Srgb);
+
+        assert_eq!(canvas.get_pixel(29, 100), &Rgba([255, 255, 255, 255]));
+        assert_eq!(canvas.get_pixel(30, 100), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(39, 100), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(40, 100), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(49, 100), &Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,691 @@
+//! The metadata module is responsible for reading the EXIF, XMP and ICC data stored alongside
+//! the pixels, and for choosing which of them are carried over to the output.
+use crate::errors::OliframeError;
+use crate::frame::color_space::ColorSpace;
+use crate::geometry::Size;
+use derive_getters::Getters;
+use exif::{Context, Exif, Field, In, Reader, Tag, Value};
+use img_parts::jpeg::markers;
+use img_parts::webp::CHUNK_XMP;
+use img_parts::{Bytes, DynImage};
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::io::Cursor;
+use std::path::Path;
+use std::str::FromStr;
+use strum_macros::{AsRefStr, EnumIter, EnumString};
+
+/// The TIFF tag holding an XMP packet.
+pub const XMP_TAG: Tag = Tag(Context::Tiff, 700);
+/// The XMP properties describing the layout of the pixels, updated to match the output.
+const XMP_LAYOUT_PROPERTIES: &str =
+    r"tiff:Orientation|tiff:ImageWidth|tiff:ImageLength|exif:PixelXDimension|exif:PixelYDimension";
+/// The TIFF tag holding an ICC color profile.
+pub const ICC_TAG: Tag = Tag(Context::Tiff, 34675);
+/// The prefix of the JPEG APP1 segment holding an XMP packet.
+pub const XMP_JPEG_PREFIX: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/// The keyword of the PNG iTXt chunk holding an XMP packet.
+pub const XMP_PNG_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+
+/// The TIFF tags describing how the pixels of the input file are laid out,
+/// which do not apply to the output file.
+const LAYOUT_TAGS: [u16; 21] = [
+    254, // NewSubfileType
+    255, // SubfileType
+    258, // BitsPerSample
+    259, // Compression
+    262, // PhotometricInterpretation
+    266, // FillOrder
+    273, // StripOffsets
+    277, // SamplesPerPixel
+    278, // RowsPerStrip
+    279, // StripByteCounts
+    284, // PlanarConfiguration
+    317, // Predictor
+    320, // ColorMap
+    322, // TileWidth
+    323, // TileLength
+    324, // TileOffsets
+    325, // TileByteCounts
+    338, // ExtraSamples
+    339, // SampleFormat
+    347, // JPEGTables
+    530, // YCbCrSubSampling
+];
+
+/// The fields that can be read from an image's metadata, by name.
+pub const FIELDS: [&str; 14] = [
+    "make",
+    "model",
+    "lens",
+    "fnumber",
+    "exposure",
+    "iso",
+    "focal",
+    "focal35",
+    "date",
+    "time",
+    "artist",
+    "copyright",
+    "software",
+    "filename",
+];
+
+/// The metadata of an image: the values of its fields formatted for display,
+/// and the raw EXIF, XMP and ICC data to carry over to the output.
+#[derive(Clone, Default)]
+pub struct Metadata {
+    /// The values of the fields found in the image, by name.
+    fields: BTreeMap<&'static str, String>,
+    /// The EXIF fields of the primary image, without those describing the layout of its pixels.
+    exif: Vec<Field>,
+    /// Whether the EXIF data was stored in little-endian byte order.
+    little_endian: bool,
+    /// The XMP packet.
+    xmp: Option<Vec<u8>>,
+    /// The ICC color profile, which only describes RGB colors,
+    /// since images tagged with other profiles are converted to sRGB when loaded.
+    icc: Option<Vec<u8>>,
+    /// The color space described by the ICC profile, when it is not sRGB.
+    color_space: Option<ColorSpace>,
+}
+
+/// The kinds of metadata carried over from each input image to its output.
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct KeepMetadata {
+    /// Whether to keep the EXIF data.
+    exif: bool,
+    /// Whether to keep the XMP packet.
+    xmp: bool,
+    /// Whether to keep the ICC color profile.
+    icc: bool,
+    /// The groups of sensitive fields stripped from the EXIF data and XMP packet.
+    strip: Vec<Sensitive>,
+}
+
+/// A group of sensitive metadata fields that can be stripped from the output.
+#[derive(AsRefStr, Clone, Copy, Debug, EnumIter, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Sensitive {
+    /// The location where the image was taken.
+    Gps,
+    /// The serial numbers of the camera and lens,
+    /// along with the maker notes, which often repeat them in a format that cannot be edited.
+    Serial,
+    /// The name of the camera's owner.
+    Owner,
+}
+
+impl Metadata {
+    /// Read the metadata of the given image file.
+    ///
+    /// Files without readable EXIF data have no fields other than their name.
+    pub fn read(path: &Path) -> Self {
+        let mut metadata = std::fs::read(path)
+            .map(|bytes| Self::from_bytes(bytes, path))
+            .unwrap_or_default();
+        if let Some(name) = path.file_name() {
+            metadata
+                .fields
+                .insert("filename", name.to_string_lossy().to_string());
+        }
+        metadata
+    }
+
+    /// Set the ICC color profile, as read by the image decoder.
+    pub fn with_icc_profile(mut self, icc: Option<Vec<u8>>) -> Self {
+        self.color_space = icc.as_deref().and_then(ColorSpace::from_icc);
+        self.icc = icc;
+        self
+    }
+
+    /// Create metadata from the given field values.
+    #[cfg(test)]
+    pub fn from_fields(fields: &[(&'static str, &str)]) -> Self {
+        Self {
+            fields: fields
+                .iter()
+                .map(|(name, value)| (*name, value.to_string()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// The value of the named field, if the image has it.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// The EXIF fields to write alongside an output image of the given size,
+    /// or none if the EXIF data is not kept.
+    ///
+    /// The pixel dimensions are updated to the size of the output,
+    /// and the orientation is reset since the output is saved upright.
+    pub fn exif(&self, size: &Size, keep: &KeepMetadata) -> Vec<Field> {
+        if !keep.exif {
+            return Vec::new();
+        }
+        self.exif
+            .iter()
+            .filter(|field| !keep.strips(field))
+            .map(|field| {
+                let value = match field.tag {
+                    Tag::ImageWidth | Tag::PixelXDimension => Value::Long(vec![size.width()]),
+                    Tag::ImageLength | Tag::PixelYDimension => Value::Long(vec![size.height()]),
+                    Tag::Orientation => Value::Short(vec![1]),
+                    _ => field.value.clone(),
+                };
+                Field {
+                    tag: field.tag,
+                    ifd_num: field.ifd_num,
+                    value,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the EXIF data was stored in little-endian byte order.
+    pub fn little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    /// The XMP packet to write alongside an output image of the given size,
+    /// if the image has one and it is kept, without the stripped properties.
+    ///
+    /// As in the EXIF data, the pixel dimensions are updated and the orientation is reset.
+    pub fn xmp(&self, size: &Size, keep: &KeepMetadata) -> Option<Vec<u8>> {
+        let xmp = self.xmp.as_deref().filter(|_| keep.xmp)?;
+        let Ok(xmp) = std::str::from_utf8(xmp) else {
+            if keep.strip.is_empty() {
+                return Some(xmp.to_vec());
+            }
+            log::warn!("The XMP packet is not valid UTF-8 and was left out.");
+            return None;
+        };
+
+        let (xmp, _) = scrub_xmp(xmp, &keep.strip);
+        let (xmp, _) = edit_xmp(&xmp, XMP_LAYOUT_PROPERTIES, |name| {
+            Some(match name {
+                "tiff:ImageWidth" | "exif:PixelXDimension" => size.width().to_string(),
+                "tiff:ImageLength" | "exif:PixelYDimension" => size.height().to_string(),
+                _ => "1".to_string(),
+            })
+        });
+        Some(xmp.into_bytes())
+    }
+
+    /// The names of the EXIF fields and XMP properties stripped from the output.
+    pub fn stripped(&self, keep: &KeepMetadata) -> Vec<String> {
+        let mut stripped: Vec<String> = self
+            .exif
+            .iter()
+            .filter(|field| keep.exif && keep.strips(field))
+            .map(|field| field.tag.to_string())
+            .collect();
+        if let Some(xmp) = self
+            .xmp
+            .as_deref()
+            .filter(|_| keep.xmp && !keep.strip.is_empty())
+        {
+            match std::str::from_utf8(xmp) {
+                Ok(xmp) => stripped.extend(scrub_xmp(xmp, &keep.strip).1),
+                Err(_) => stripped.push("XMP packet".to_string()),
+            }
+        }
+        stripped
+    }
+
+    /// The ICC color profile, if the image has one and it is kept.
+    pub fn icc(&self, keep: &KeepMetadata) -> Option<&[u8]> {
+        self.icc.as_deref().filter(|_| keep.icc)
+    }
+
+    /// The color space of the image's pixels, if it is tagged with a profile other than sRGB.
+    pub fn color_space(&self) -> Option<&ColorSpace> {
+        self.color_space.as_ref()
+    }
+
+    /// Read the metadata from the contents of an image file.
+    fn from_bytes(bytes: Vec<u8>, path: &Path) -> Self {
+        let mut metadata = Reader::new()
+            .read_from_container(&mut Cursor::new(&bytes))
+            .inspect_err(|e| log::debug!("No EXIF data in {path:?}: {e}"))
+            .map(|exif| Self::from_exif(&exif))
+            .unwrap_or_default();
+        if metadata.xmp.is_none() {
+            metadata.xmp = read_xmp(bytes);
+        }
+        metadata
+    }
+
+    /// Extract and format the known fields from EXIF data.
+    fn from_exif(exif: &Exif) -> Self {
+        let value = |tag| exif.get_field(tag, In::PRIMARY).map(|field| &field.value);
+        let date_time = value(Tag::DateTimeOriginal)
+            .or_else(|| value(Tag::DateTime))
+            .and_then(ascii);
+        let date_time = date_time.as_deref().and_then(|dt| dt.split_once(' '));
+
+        let fields = [
+            ("make", value(Tag::Make).and_then(ascii)),
+            ("model", value(Tag::Model).and_then(ascii)),
+            ("lens", value(Tag::LensModel).and_then(ascii)),
+            ("fnumber", value(Tag::FNumber).and_then(decimal)),
+            ("exposure", value(Tag::ExposureTime).and_then(exposure)),
+            (
+                "iso",
+                value(Tag::PhotographicSensitivity)
+                    .and_then(|v| v.get_uint(0))
+                    .map(|iso| iso.to_string()),
+            ),
+            ("focal", value(Tag::FocalLength).and_then(decimal)),
+            (
+                "focal35",
+                value(Tag::FocalLengthIn35mmFilm)
+                    .and_then(|v| v.get_uint(0))
+                    .filter(|focal| *focal > 0)
+                    .map(|focal| focal.to_string()),
+            ),
+            ("date", date_time.map(|(date, _)| date.replace(':', "-"))),
+            (
+                "time",
+                date_time.map(|(_, time)| time.get(..5).unwrap_or(time).to_string()),
+            ),
+            ("artist", value(Tag::Artist).and_then(ascii)),
+            ("copyright", value(Tag::Copyright).and_then(ascii)),
+            ("software", value(Tag::Software).and_then(ascii)),
+        ];
+
+        // TIFF files store their XMP packet alongside the EXIF fields.
+        let xmp = exif
+            .get_field(XMP_TAG, In::PRIMARY)
+            .and_then(|field| match &field.value {
+                Value::Byte(bytes) | Value::Undefined(bytes, _) => Some(bytes.clone()),
+                _ => None,
+            });
+
+        Self {
+            fields: fields
+                .into_iter()
+                .filter_map(|(name, value)| value.map(|value| (name, value)))
+                .collect(),
+            exif: exif
+                .fields()
+                .filter(|field| field.ifd_num == In::PRIMARY && is_carried(field))
+                .cloned()
+                .collect(),
+            little_endian: exif.little_endian(),
+            xmp,
+            icc: None,
+            color_space: None,
+        }
+    }
+}
+
+impl Debug for Metadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metadata")
+            .field("fields", &self.fields)
+            .field("exif", &self.exif.len())
+            .field("xmp", &self.xmp.as_ref().map(Vec::len))
+            .field("icc", &self.icc.as_ref().map(Vec::len))
+            .field("color_space", &self.color_space)
+            .finish()
+    }
+}
+
+impl KeepMetadata {
+    /// Set the groups of sensitive fields stripped from the EXIF data and XMP packet.
+    pub fn with_strip(mut self, strip: Vec<Sensitive>) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    /// Whether the EXIF field belongs to one of the stripped groups.
+    fn strips(&self, field: &Field) -> bool {
+        self.strip.iter().any(|group| group.contains(field))
+    }
+
+    /// Keep nothing.
+    fn none() -> Self {
+        Self {
+            exif: false,
+            xmp: false,
+            icc: false,
+            strip: Vec::new(),
+        }
+    }
+}
+
+impl Default for KeepMetadata {
+    fn default() -> Self {
+        Self {
+            exif: true,
+            xmp: true,
+            icc: true,
+            strip: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for KeepMetadata {
+    type Err = OliframeError;
+
+    /// Parse the kinds of metadata to keep: "all", "none",
+    /// or a comma-separated list of "exif", "xmp" and "icc".
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "all" => return Ok(Self::default()),
+            "none" => return Ok(Self::none()),
+            _ => {}
+        }
+
+        let mut keep = Self::none();
+        for kind in input.split(',').map(|kind| kind.trim().to_lowercase()) {
+            match kind.as_str() {
+                "exif" => keep.exif = true,
+                "xmp" => keep.xmp = true,
+                "icc" => keep.icc = true,
+                _ => {
+                    return Err(OliframeError::InvalidInput(format!(
+                        "Invalid metadata: {kind}.  Use \"all\", \"none\", \
+                        or a list of \"exif\", \"xmp\" and \"icc\"."
+                    )));
+                }
+            }
+        }
+        Ok(keep)
+    }
+}
+
+impl Sensitive {
+    /// Whether the EXIF field belongs to this group.
+    fn contains(&self, field: &Field) -> bool {
+        match self {
+            Self::Gps => field.tag.0 == Context::Gps,
+            Self::Serial => matches!(
+                field.tag,
+                Tag::BodySerialNumber | Tag::LensSerialNumber | Tag::MakerNote
+            ),
+            Self::Owner => field.tag == Tag::CameraOwnerName,
+        }
+    }
+
+    /// A regular expression matching the names of the XMP properties in this group.
+    fn xmp_properties(&self) -> &'static str {
+        match self {
+            Self::Gps => r"exif:GPS\w+",
+            Self::Serial => {
+                r"aux:SerialNumber|aux:LensSerialNumber|exifEX:BodySerialNumber|exifEX:LensSerialNumber"
+            }
+            Self::Owner => r"aux:OwnerName|exifEX:CameraOwnerName",
+        }
+    }
+}
+
+/// Whether an EXIF field is carried over to the output.
+///
+/// Fields describing the layout of the input's pixels are left out, along with the pointers
+/// between directories, which are rebuilt when writing.  The XMP packet and ICC profile are
+/// carried separately, and fields of unknown types cannot be written.
+fn is_carried(field: &Field) -> bool {
+    let Tag(context, number) = field.tag;
+    let layout = context == Context::Tiff && LAYOUT_TAGS.contains(&number);
+    let pointer = matches!(
+        field.tag,
+        Tag::ExifIFDPointer | Tag::GPSInfoIFDPointer | Tag::InteropIFDPointer
+    );
+    !layout
+        && !pointer
+        && field.tag != XMP_TAG
+        && field.tag != ICC_TAG
+        && !matches!(field.value, Value::Unknown(..))
+}
+
+/// Remove the properties in the given groups from an XMP packet, whether they are written
+/// as attributes or as elements, returning the scrubbed packet and the names of those removed.
+fn scrub_xmp(xmp: &str, strip: &[Sensitive]) -> (String, Vec<String>) {
+    if strip.is_empty() {
+        return (xmp.to_string(), Vec::new());
+    }
+    let names = strip
+        .iter()
+        .map(Sensitive::xmp_properties)
+        .collect::<Vec<_>>()
+        .join("|");
+    edit_xmp(xmp, &names, |_| None)
+}
+
+/// Rewrite the properties of an XMP packet whose names match the given pattern,
+/// whether they are written as attributes or as elements.
+///
+/// Each property is set to the value given for its name, or removed if there is none.
+/// Returns the edited packet and the names of the properties found.
+fn edit_xmp(
+    xmp: &str,
+    names: &str,
+    value: impl Fn(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let attribute = Regex::new(&format!(r#"(\s+)((?:{names}))\s*=\s*(?:"[^"]*"|'[^']*')"#))
+        .expect("Invalid regex");
+    let element = Regex::new(&format!(
+        r"(?s)(\s*)<((?:{names}))(?:\s[^>]*)?(?:/>|>.*?</(?:{names})>)"
+    ))
+    .expect("Invalid regex");
+
+    let mut found = Vec::new();
+    let mut edited = xmp.to_string();
+    for (re, is_attribute) in [(&attribute, true), (&element, false)] {
+        edited = re
+            .replace_all(&edited, |captures: &Captures| {
+                let (space, name) = (&captures[1], &captures[2]);
+                found.push(name.to_string());
+                match value(name) {
+                    Some(value) if is_attribute => format!(r#"{space}{name}="{value}""#),
+                    Some(value) => format!("{space}<{name}>{value}</{name}>"),
+                    None => String::new(),
+                }
+            })
+            .into_owned();
+    }
+    (edited, found)
+}
+
+/// Read the XMP packet from the contents of a JPEG, PNG or WebP file.
+fn read_xmp(bytes: Vec<u8>) -> Option<Vec<u8>> {
+    match DynImage::from_bytes(Bytes::from(bytes)).ok().flatten()? {
+        DynImage::Jpeg(jpeg) => jpeg
+            .segments_by_marker(markers::APP1)
+            .find_map(|segment| segment.contents().strip_prefix(XMP_JPEG_PREFIX))
+            .map(<[u8]>::to_vec),
+        DynImage::Png(png) => png
+            .chunks_by_type(*b"iTXt")
+            .find_map(|chunk| itxt_xmp(chunk.contents())),
+        DynImage::WebP(webp) => webp
+            .chunk_by_id(CHUNK_XMP)
+            .and_then(|chunk| chunk.content().data())
+            .map(|data| data.to_vec()),
+    }
+}
+
+/// Read the XMP packet from the contents of a PNG iTXt chunk, if it holds one.
+///
+/// The chunk holds the keyword, the compression flag and method, the language tag
+/// and the translated keyword, before the text itself.  Compressed packets are left out.
+fn itxt_xmp(contents: &[u8]) -> Option<Vec<u8>> {
+    let rest = contents
+        .strip_prefix(XMP_PNG_KEYWORD)?
+        .strip_prefix(b"\0")?;
+    let ([0, _], rest) = rest.split_at_checked(2)? else {
+        return None;
+    };
+    rest.splitn(3, |byte| *byte == 0).nth(2).map(<[u8]>::to_vec)
+}
+
+/// Format a text value, ignoring padding and empty values.
+fn ascii(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(lines) => lines
+            .first()
+            .map(|line| String::from_utf8_lossy(line).trim().to_string())
+            .filter(|text| !text.is_empty()),
+        _ => None,
+    }
+}
+
+/// Format a rational value as a decimal with at most one decimal place (e.g.: "5.6" or "2").
+fn decimal(value: &Value) -> Option<String> {
+    match value {
+        Value::Rational(values) => values.first().filter(|r| r.denom != 0).map(|r| {
+            format!("{:.1}", r.to_f64())
+                .trim_end_matches(".0")
+                .to_string()
+        }),
+        _ => None,
+    }
+}
+
+/// Format an exposure time in seconds as a fraction (e.g.: "1/250") or a decimal (e.g.: "2.5").
+fn exposure(value: &Value) -> Option<String> {
+    match value {
+        Value::Rational(values) => values
+            .first()
+            .filter(|r| r.num != 0 && r.denom != 0)
+            .map(|r| {
+                if r.num >= r.denom {
+                    format!("{:.1}", r.to_f64())
+                        .trim_end_matches(".0")
+                        .to_string()
+                } else {
+                    format!("1/{}", (r.denom as f64 / r.num as f64).round())
+                }
+            }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::Rational;
+
+    #[test]
+    fn read_exif_fields() {
+        let metadata = Metadata::read(Path::new("images/test/test_01.jpg"));
+        assert_eq!(metadata.get("make"), Some("FUJIFILM"));
+        assert_eq!(metadata.get("model"), Some("X100V"));
+        assert_eq!(metadata.get("fnumber"), Some("5.6"));
+        assert_eq!(metadata.get("exposure"), Some("1/60"));
+        assert_eq!(metadata.get("iso"), Some("2500"));
+        assert_eq!(metadata.get("focal"), Some("23"));
+        assert_eq!(metadata.get("date"), Some("2024-08-16"));
+        assert_eq!(metadata.get("time"), Some("20:51"));
+        assert_eq!(metadata.get("artist"), Some("Robert M Chambers"));
+        assert_eq!(metadata.get("filename"), Some("test_01.jpg"));
+        assert_eq!(metadata.get("lens"), None);
+    }
+
+    #[test]
+    fn read_png_exif_fields() {
+        let metadata = Metadata::read(Path::new("images/test/test_03.png"));
+        assert_eq!(metadata.get("fnumber"), Some("2"));
+    }
+
+    #[test]
+    fn files_without_exif_have_only_a_name() {
+        let metadata = Metadata::read(Path::new("Cargo.toml"));
+        assert_eq!(
+            metadata.fields,
+            Metadata::from_fields(&[("filename", "Cargo.toml")]).fields
+        );
+        assert!(metadata.exif.is_empty());
+        assert_eq!(metadata.xmp, None);
+    }
+
+    #[test]
+    fn strip_sensitive_fields() {
+        let mut metadata = Metadata::read(Path::new("images/test/test_01.jpg"));
+        metadata.exif.push(Field {
+            tag: Tag::GPSLatitudeRef,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"N".to_vec()]),
+        });
+        let size = Size::new(10, 10);
+        let tags = |keep: &KeepMetadata| -> Vec<Tag> {
+            metadata
+                .exif(&size, keep)
+                .iter()
+                .map(|field| field.tag)
+                .collect()
+        };
+
+        let keep = KeepMetadata::default();
+        assert!(tags(&keep).contains(&Tag::BodySerialNumber));
+        assert!(tags(&keep).contains(&Tag::GPSLatitudeRef));
+        assert!(metadata.stripped(&keep).is_empty());
+
+        let keep = KeepMetadata::default().with_strip(vec![Sensitive::Gps, Sensitive::Serial]);
+        assert!(!tags(&keep).contains(&Tag::BodySerialNumber));
+        assert!(!tags(&keep).contains(&Tag::GPSLatitudeRef));
+        assert!(tags(&keep).contains(&Tag::Model));
+        let stripped = metadata.stripped(&keep);
+        assert!(stripped.contains(&"BodySerialNumber".to_string()));
+        assert!(stripped.contains(&"GPSLatitudeRef".to_string()));
+    }
+
+    #[test]
+    fn scrub_xmp_properties() {
+        let xmp = r#"<rdf:Description exif:GPSLatitude="41,24.5N" aux:SerialNumber='123'
+            xmp:Rating="5">
+            <exif:GPSLongitude>2,10.2E</exif:GPSLongitude>
+            <aux:OwnerName>Jane</aux:OwnerName>
+            <dc:creator>Jane</dc:creator>
+        </rdf:Description>"#;
+
+        let (scrubbed, removed) = scrub_xmp(xmp, &[Sensitive::Gps, Sensitive::Owner]);
+        assert_eq!(
+            removed,
+            vec!["exif:GPSLatitude", "exif:GPSLongitude", "aux:OwnerName"]
+        );
+        assert!(!scrubbed.contains("GPS") && !scrubbed.contains("OwnerName"));
+        assert!(scrubbed.contains(r#"aux:SerialNumber='123'"#));
+        assert!(scrubbed.contains(r#"xmp:Rating="5""#));
+        assert!(scrubbed.contains("<dc:creator>Jane</dc:creator>"));
+    }
+
+    #[test]
+    fn update_xmp_layout() {
+        let metadata = Metadata {
+            xmp: Some(
+                br#"<rdf:Description tiff:Orientation="6" exif:PixelXDimension="4000"
+                aux:SerialNumber="123">
+                <exif:PixelYDimension>3000</exif:PixelYDimension>
+                <tiff:ImageWidth>4000</tiff:ImageWidth>
+            </rdf:Description>"#
+                    .to_vec(),
+            ),
+            ..Metadata::default()
+        };
+        let size = Size::new(640, 480);
+        let keep = KeepMetadata::default().with_strip(vec![Sensitive::Serial]);
+        let xmp = String::from_utf8(metadata.xmp(&size, &keep).unwrap()).unwrap();
+        assert!(xmp.contains(r#" tiff:Orientation="1""#));
+        assert!(xmp.contains(r#" exif:PixelXDimension="640""#));
+        assert!(xmp.contains("<exif:PixelYDimension>480</exif:PixelYDimension>"));
+        assert!(xmp.contains("<tiff:ImageWidth>640</tiff:ImageWidth>"));
+        assert!(!xmp.contains("SerialNumber"));
+    }
+
+    #[test]
+    fn format_values() {
+        let rational = |num, denom| Value::Rational(vec![Rational { num, denom }]);
+        assert_eq!(exposure(&rational(1, 250)).as_deref(), Some("1/250"));
+        assert_eq!(exposure(&rational(10, 30)).as_deref(), Some("1/3"));
+        assert_eq!(exposure(&rational(5, 2)).as_deref(), Some("2.5"));
+        assert_eq!(exposure(&rational(0, 1)), None);
+        assert_eq!(decimal(&rational(28, 10)).as_deref(), Some("2.8"));
+        assert_eq!(decimal(&rational(35, 1)).as_deref(), Some("35"));
+        assert_eq!(ascii(&Value::Ascii(vec![b"  ".to_vec()])), None);
+    }
+}
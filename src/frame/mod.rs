@@ -1,15 +1,48 @@
 //! The frame module is responsible for framing images based on configuration values.
+mod antialias;
+mod background;
 mod build;
+mod caption;
+mod color_space;
+mod composite;
+mod encode;
+mod fill;
+mod keyline;
+mod mask;
+mod mat;
+mod metadata;
+mod outline;
 mod pixel_source;
+mod resample;
+mod shadow;
+mod template;
+mod texture;
+mod watermark;
+
+pub use antialias::Antialias;
+pub use background::Background;
+pub use caption::Caption;
+pub use composite::{Blending, Transparency};
+pub use fill::Fill;
+pub use keyline::Keyline;
+pub use mask::Mask;
+pub use mat::Mat;
+pub use metadata::{KeepMetadata, Sensitive};
+pub(crate) use outline::Outline;
+pub use resample::Resampling;
+pub use shadow::Shadow;
+pub use template::Template;
+pub use texture::{Texture, TextureFit};
+pub use watermark::{Watermark, WatermarkArea};
 
 use crate::config::FrameConfig;
 use crate::errors::OliframeError;
 use crate::file_collector::FilePair;
-use crate::frame::pixel_source::PixelSource;
+use crate::frame::metadata::Metadata;
 use crate::geometry::*;
 use derive_getters::Getters;
 use image::buffer::ConvertBuffer;
-use image::{DynamicImage, GenericImageView, ImageFormat, Pixel, RgbaImage};
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbaImage};
 use std::path::PathBuf;
 
 /// A single image being processed.
@@ -20,14 +53,21 @@ pub struct Frame {
     img: DynamicImage,
     /// The format of the image.
     fmt: ImageFormat,
-    /// The size of the input image.
-    input_size: Size,
+    /// The metadata read alongside the image, used to fill caption templates
+    /// and carried over to the output.
+    metadata: Metadata,
     /// The path to save the output image.
     output_path: PathBuf,
     /// The size of the output image.
     output_size: Size,
     /// The location of the image's top-left corner within the frame.
     position: Point,
+    /// The dimension that relative mats are a fraction of.
+    margin_dimension: f32,
+    /// The shape of the window through which the image is seen.
+    window: Outline,
+    /// The shape of the whole output image.
+    canvas: Outline,
 }
 
 impl Frame {
@@ -38,83 +78,234 @@ impl Frame {
         dry_run: bool,
     ) -> Result<(), OliframeError> {
         log::debug!("Processing image: {:?}", file_pair.input_path());
-        Self::build(file_pair, config)?.draw(config).save(dry_run)
+        Self::build(file_pair, config)?
+            .draw(config)
+            .save(config, dry_run)
     }
 
     /// Build a new frame from the given file pair and configuration.
     fn build(file_pair: FilePair, config: &FrameConfig) -> Result<Self, OliframeError> {
-        let (img, fmt) = build::load(file_pair.input_path())?;
+        let (mut img, fmt, metadata) = build::load(file_pair.input_path())?;
+        if let Some(size) = config.size() {
+            let fitted = build::fit_to_size(&Size::from(img.dimensions()), size, config)
+                .ok_or_else(|| {
+                    OliframeError::InvalidInput(format!(
+                        "The frame leaves no room for the image in a {}x{} output.",
+                        size.width(),
+                        size.height()
+                    ))
+                })?;
+            if fitted.dimensions() != img.dimensions() {
+                log::debug!("Resizing image to {fitted:?} to fit the output size");
+                img = img.resize_exact(
+                    fitted.width(),
+                    fitted.height(),
+                    config.resampling().filter(),
+                );
+            }
+        }
+        if let (Some(aspect_ratio), AspectRatioMode::Crop) =
+            (config.aspect_ratio(), config.aspect_ratio_mode())
+        {
+            let input_size = Size::from(img.dimensions());
+            let crop = build::crop_to_ratio(&input_size, aspect_ratio, config);
+            let at = build::crop_position(
+                &input_size,
+                &crop,
+                config.position(),
+                config.focus().as_ref(),
+            );
+            log::debug!("Cropping image to {crop:?} at {at:?} to fit the aspect ratio");
+            img = img.crop_imm(at.x(), at.y(), crop.width(), crop.height());
+        }
         let input_size = Size::from(img.dimensions());
-        let border = config.margins().to_border_with_size(&input_size);
+        let margin_dimension = build::margin_dimension(&input_size, config);
+        let border = build::border(&input_size, config);
         let (_, output_path) = file_pair.into_parts();
-        let output_size = build::output_dimensions(&input_size, &border, config);
+        let output_size = build::output_dimensions(&input_size, &border, config)?;
         let position = build::position(&input_size, &output_size, config.position(), &border);
+        let radii = config
+            .corner_radius()
+            .as_ref()
+            .map(|radius| radius.to_radii_with_size(&input_size))
+            .unwrap_or_default()
+            .with_shape(*config.corner_shape());
+        let window = Outline::new(
+            position.clone(),
+            input_size.clone(),
+            radii,
+            config.mask().clone(),
+            *config.antialias(),
+        );
+        let canvas_radii = config
+            .canvas_radius()
+            .as_ref()
+            .map(|radius| radius.to_radii_with_size(&output_size))
+            .unwrap_or_default()
+            .with_shape(*config.corner_shape());
+        let canvas = Outline::new(
+            Point::new(0, 0),
+            output_size.clone(),
+            canvas_radii,
+            Mask::default(),
+            *config.antialias(),
+        );
 
         Ok(Self {
             img,
             fmt,
-            input_size,
+            metadata,
             output_path,
             output_size,
             position,
+            margin_dimension,
+            window,
+            canvas,
         })
     }
 
     /// Draw the frame around the image.
+    ///
+    /// Frame colors are given in sRGB. For an image tagged with another color profile,
+    /// the frame is converted into the image's color space when the profile is kept,
+    /// and the image is converted to sRGB when it is not.
     pub fn draw(mut self, config: &FrameConfig) -> Self {
-        let output = RgbaImage::from_fn(
+        let mut color_space = self.metadata.color_space().cloned();
+        if let Some(space) = color_space.as_ref() {
+            if config.metadata().icc() {
+                log::debug!("Converting frame colors into {space:?}");
+            } else {
+                log::debug!("Converting image from {space:?} to sRGB");
+                self.img = space.dynamic_to_srgb(&self.img);
+                color_space = None;
+            }
+        }
+
+        let mut backdrop = config.background().render(
+            &self.img,
+            color_space.as_ref(),
+            &self.output_size,
+            config.color(),
+            *config.blending(),
+        );
+        mat::paint_all(
+            config.mats(),
+            &mut backdrop,
+            self.window(),
+            self.margin_dimension,
+            *config.blending(),
+        );
+        if let Some(shadow) = config.shadow() {
+            shadow.paint(&mut backdrop, self.window(), *config.blending());
+        }
+        if let Some(keyline) = config.keyline() {
+            keyline.paint(&mut backdrop, self.window(), *config.blending());
+        }
+        if let Some(caption) = config.caption() {
+            caption.paint(
+                &mut backdrop,
+                self.window(),
+                &build::inner_border(self.window().size(), config),
+                self.metadata(),
+                *config.blending(),
+            );
+        }
+        if let Some(color_space) = color_space.as_ref() {
+            color_space.image_from_srgb(&mut backdrop);
+        }
+        let mut output = RgbaImage::from_fn(
             self.output_size.width(),
             self.output_size.height(),
             |x, y| {
-                let pixel = Point::new(x, y);
-                match PixelSource::at(
-                    pixel,
-                    self.position(),
-                    self.input_size(),
-                    config.corner_radius(),
-                ) {
-                    PixelSource::Image => self
+                let mut color = *backdrop.get_pixel(x, y);
+                let coverage = self.window.coverage(x, y);
+                if coverage > 0. {
+                    let img = self
                         .img
-                        .get_pixel(x - self.position.x(), y - self.position.y()),
-                    PixelSource::Background => *config.color(),
-                    PixelSource::Blend(alpha) => self
-                        .img
-                        .get_pixel(x - self.position.x(), y - self.position.y())
-                        .map2(config.color(), |img, bkg| {
-                            (img as f32 * alpha + bkg as f32 * (1.0 - alpha)) as u8
-                        }),
+                        .get_pixel(x - self.position.x(), y - self.position.y());
+                    match config.transparency() {
+                        Transparency::Flatten => {
+                            composite::over(&mut color, &img, coverage, *config.blending())
+                        }
+                        Transparency::Keep => {
+                            color = composite::mix(&img, &color, coverage, *config.blending())
+                        }
+                    }
+                }
+                // Pixels outside the rounded canvas are left (partly) transparent.
+                let coverage = self.canvas.coverage(x, y);
+                if coverage < 1. {
+                    color[3] = (color[3] as f32 * coverage).round() as u8;
                 }
+                color
             },
         );
 
+        if let Some(watermark) = config.watermark() {
+            watermark.paint(
+                &mut output,
+                self.window(),
+                color_space.as_ref(),
+                *config.blending(),
+            );
+        }
+
         self.img = match self.fmt {
-            ImageFormat::Jpeg => DynamicImage::ImageRgb8(output.convert()),
+            ImageFormat::Jpeg => {
+                let matte = color_space.as_ref().map_or(*config.matte(), |space| {
+                    space.color_from_srgb(*config.matte())
+                });
+                DynamicImage::ImageRgb8(
+                    composite::flatten(&output, &matte, *config.blending()).convert(),
+                )
+            }
             _ => DynamicImage::ImageRgba8(output),
         };
 
         self
     }
 
-    /// Save the image to the output path.
-    fn save(&self, dry_run: bool) -> Result<(), OliframeError> {
+    /// Save the image to the output path, along with the metadata kept from the input.
+    fn save(&self, config: &FrameConfig, dry_run: bool) -> Result<(), OliframeError> {
+        let stripped = self.metadata().stripped(config.metadata());
         if dry_run {
             log::info!("Dry run: Would save image to {:?}", self.output_path());
+            if !stripped.is_empty() {
+                log::info!(
+                    "Dry run: Would remove {} from {:?}",
+                    stripped.join(", "),
+                    self.output_path()
+                );
+            }
             return Ok(());
         }
+        if !stripped.is_empty() {
+            log::debug!(
+                "Removing {} from {:?}",
+                stripped.join(", "),
+                self.output_path()
+            );
+        }
 
-        self.img
-            .save(self.output_path())
-            .map_err(|e| OliframeError::SaveError(self.output_path().clone(), e.to_string()))
+        let save_error = |e: String| OliframeError::SaveError(self.output_path().clone(), e);
+        let bytes = encode::encode(&self.img, self.fmt, self.metadata(), config.metadata())
+            .map_err(save_error)?;
+        std::fs::write(self.output_path(), bytes).map_err(|e| save_error(e.to_string()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::TEST_FS_PREFIX;
-    use image::Rgba;
+    use crate::frame::color_space::ColorSpace;
+    use crate::test_utils::{
+        TEST_FS_PREFIX, save_jpeg_with_orientation, save_png_with_icc_profile,
+    };
+    use image::metadata::Orientation;
+    use image::{ImageDecoder, ImageReader, RgbImage, Rgba};
     use std::fs::File;
     use std::io::{BufReader, Read};
+    use std::str::FromStr;
     use tempfile::TempDir;
     use xxhash_rust::xxh3::Xxh3;
 
@@ -130,7 +321,7 @@ mod tests {
         let result = Frame::process(file_pair, &config, false);
         assert!(result.is_ok());
         assert_eq!(
-            "ecc66af0173bb87c".to_string(),
+            "677febca13882bfb".to_string(),
             hash_file(&temp_dir.path().join("test_01.jpg"))
         );
     }
@@ -141,8 +332,8 @@ mod tests {
         let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
         let config = FrameConfig::new(
             None,
-            Rgba([255, 255, 255, 255]),
-            Some(10),
+            Fill::Solid(Rgba([255, 255, 255, 255])),
+            Some(CornerRadius::from_str("10").unwrap()),
             Margins::default(),
             RelativePosition::default(),
         );
@@ -155,7 +346,7 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(
             hash_file(&temp_dir.path().join("test_02.jpeg")),
-            "136bf883d5ad6df6".to_string()
+            "181eb61648b5ee4c".to_string()
         );
     }
 
@@ -172,8 +363,498 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(
             hash_file(&temp_dir.path().join("test_03.png")),
-            "951f8ff4fa2f61bb".to_string()
+            "2bb59f5abb905a5".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_blurred_background() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config = FrameConfig::default().with_background(Background::Blur {
+            radius: 0.05,
+            dim: 0.2,
+        });
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_01.jpg"),
+            temp_dir.path().join("test_01.jpg"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_01.jpg")),
+            "52f858ef6468bdc".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_texture_and_corner_radius() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let texture =
+            Texture::load(&PathBuf::from("images/test/test_03.png"), TextureFit::Tile).unwrap();
+        let config = FrameConfig::new(
+            None,
+            Fill::default(),
+            Some(CornerRadius::from_str("10").unwrap()),
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_background(Background::Texture(texture));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_02.jpeg"),
+            temp_dir.path().join("test_02.jpeg"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_02.jpeg")),
+            "3ebc98e524e0892c".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_shadow() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let shadow = Shadow::from_str("10 10 20")
+            .unwrap()
+            .with_color(Rgba([0, 0, 0, 255]), 50.)
+            .unwrap();
+        let config = FrameConfig::new(
+            None,
+            Fill::default(),
+            Some(CornerRadius::from_str("10").unwrap()),
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_shadow(Some(shadow));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_03.png"),
+            temp_dir.path().join("test_03.png"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_03.png")),
+            "eb9569c137cbfad".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_keyline() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let keyline = Keyline::new(
+            Length::from_str("0.5%").unwrap(),
+            Length::from_str("4px").unwrap(),
+            Rgba([0, 0, 0, 255]),
+        );
+        let config = FrameConfig::new(
+            Some(AspectRatio::from_str("1:1").unwrap()),
+            Fill::default(),
+            Some(CornerRadius::from_str("10").unwrap()),
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_keyline(Some(keyline));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_02.jpeg"),
+            temp_dir.path().join("test_02.jpeg"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_02.jpeg")),
+            "42b2f7e8ed4e4904".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_mats() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let mats = vec![
+            Mat::from_str("2:black").unwrap(),
+            Mat::from_str("1.5:gold").unwrap(),
+        ];
+        let config = FrameConfig::new(
+            Some(AspectRatio::from_str("4:5").unwrap()),
+            Fill::default(),
+            None,
+            Margins::from_str("6").unwrap(),
+            RelativePosition::default(),
+        )
+        .with_mats(mats);
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_01.jpg"),
+            temp_dir.path().join("test_01.jpg"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_01.jpg")),
+            "734f01b4b2a6730e".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_squircle_corners() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config = FrameConfig::new(
+            None,
+            Fill::default(),
+            Some(CornerRadius::from_str("10%").unwrap()),
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_corner_shape(CornerShape::Squircle(5.));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_02.jpeg"),
+            temp_dir.path().join("test_02.jpeg"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_02.jpeg")),
+            "5877ff15019348f2".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_circle_mask_and_shadow() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let shadow = Shadow::from_str("0 6 12")
+            .unwrap()
+            .with_color(Rgba([0, 0, 0, 255]), 50.)
+            .unwrap();
+        let config = FrameConfig::new(
+            Some(AspectRatio::from_str("1:1").unwrap()),
+            Fill::default(),
+            None,
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_mask(Mask::Circle)
+        .with_shadow(Some(shadow));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_03.png"),
+            temp_dir.path().join("test_03.png"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_03.png")),
+            "ddbc8b52cb5e9bdb".to_string()
+        );
+    }
+
+    #[test]
+    fn process_image_with_caption() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let font =
+            Caption::load_font(&PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")).unwrap();
+        let caption = Caption::new(
+            Template::from_str("oliframe").unwrap(),
+            font,
+            Length::from_str("24px").unwrap(),
+            Rgba([40, 40, 40, 255]),
+            RelativePosition::from_str("left").unwrap(),
+            Side::Bottom,
+        )
+        .with_grow(true);
+        let config = FrameConfig::default().with_caption(Some(caption));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_03.png"),
+            temp_dir.path().join("test_03.png"),
+        );
+
+        let result = Frame::process(file_pair, &config, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            hash_file(&temp_dir.path().join("test_03.png")),
+            "9e9d8a5f75c51137".to_string()
+        );
+    }
+
+    #[test]
+    fn caption_templates_are_filled_from_exif() {
+        let font =
+            Caption::load_font(&PathBuf::from("images/test/fonts/DejaVuSansMono.ttf")).unwrap();
+        let draw = |template: &str| {
+            let caption = Caption::new(
+                Template::from_str(template).unwrap(),
+                font.clone(),
+                Length::from_str("40%").unwrap(),
+                Rgba([0, 0, 0, 255]),
+                RelativePosition::default(),
+                Side::Bottom,
+            );
+            let config = FrameConfig::default().with_caption(Some(caption));
+            let file_pair = FilePair::new(
+                PathBuf::from("images/test/test_03.png"),
+                PathBuf::from("test_03.png"),
+            );
+            Frame::build(file_pair, &config).unwrap().draw(&config).img
+        };
+
+        let filled = draw("{make} {model}[ · {lens}] · f/{fnumber} · {exposure}s · ISO {iso}");
+        assert_eq!(filled, draw("FUJIFILM X100V · f/2 · 1/60s · ISO 2500"));
+        assert_ne!(filled, draw(""));
+    }
+
+    #[test]
+    fn watermark_is_painted_over_the_image() {
+        let watermark = Watermark::new(
+            RgbaImage::from_pixel(4, 2, Rgba([255, 0, 0, 255])),
+            Length::from_str("10px").unwrap(),
+            Length::from_str("0").unwrap(),
+            RelativePosition::from_str("top left").unwrap(),
+            WatermarkArea::Image,
+        );
+        let config = FrameConfig::default().with_watermark(Some(watermark));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_03.png"),
+            PathBuf::from("test_03.png"),
+        );
+
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        let (x, y) = (frame.position().x(), frame.position().y());
+        assert_eq!(frame.img().get_pixel(x + 5, y + 5), Rgba([255, 0, 0, 255]));
+        assert_ne!(frame.img().get_pixel(x + 5, y + 15), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn oriented_images_are_framed_upright() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("portrait.jpg");
+        save_jpeg_with_orientation(&input, &RgbImage::new(80, 40), 6);
+        let output = temp_dir.path().join("framed.jpg");
+        let config = FrameConfig::default();
+        let file_pair = FilePair::new(input, output.clone());
+
+        let frame = Frame::build(file_pair, &config).unwrap();
+        assert_eq!(frame.window().size(), &Size::new(40, 80));
+        assert!(frame.output_size().height() > frame.output_size().width());
+
+        frame.draw(&config).save(&config, false).unwrap();
+        let mut decoder = ImageReader::open(&output).unwrap().into_decoder().unwrap();
+        assert_eq!(decoder.orientation().unwrap(), Orientation::NoTransforms);
+    }
+
+    #[test]
+    fn process_image_to_exact_size() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config = FrameConfig::default()
+            .with_size(Some(Size::new(1080, 1350)))
+            .with_resampling(Resampling::Cubic);
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_01.jpg"),
+            temp_dir.path().join("test_01.jpg"),
+        );
+
+        let frame = Frame::build(file_pair, &config).unwrap();
+        assert_eq!(frame.output_size(), &Size::new(1080, 1350));
+        let window = frame.window().size().clone();
+        assert!(window.width() < 1080 && window.height() < 1350);
+        // The image and its margins fill the width, and the remaining height is split evenly.
+        assert_eq!(frame.position().x() * 2 + window.width(), 1080);
+        assert_eq!(frame.position().y(), (1350 - window.height()) / 2);
+
+        let frame = frame.draw(&config);
+        assert_eq!(frame.img().dimensions(), (1080, 1350));
+    }
+
+    #[test]
+    fn crop_image_to_aspect_ratio() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config = FrameConfig::new(
+            Some(AspectRatio::from_str("4:5").unwrap()),
+            Fill::Solid(Rgba([255, 255, 255, 255])),
+            None,
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_aspect_ratio_mode(AspectRatioMode::Crop, None);
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_01.jpg"),
+            temp_dir.path().join("test_01.jpg"),
         );
+
+        let frame = Frame::build(file_pair, &config).unwrap();
+        let (width, height) = frame.output_size().dimensions();
+        assert!((width as f32 / height as f32 - 0.8).abs() < 0.01);
+        // The margins keep the same thickness on every side, give or take the rounding.
+        let (x, y) = (frame.position().x(), frame.position().y());
+        let window = frame.window().size();
+        assert!(x.abs_diff(y) <= 1);
+        assert!(x.abs_diff(width - x - window.width()) <= 1);
+        assert!(y.abs_diff(height - y - window.height()) <= 1);
+    }
+
+    #[test]
+    fn process_image_with_rounded_canvas() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config =
+            FrameConfig::default().with_canvas_radius(Some(CornerRadius::from_str("5%").unwrap()));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_03.png"),
+            temp_dir.path().join("test_03.png"),
+        );
+
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        let (width, height) = frame.img().dimensions();
+        assert_eq!(frame.img().get_pixel(0, 0)[3], 0);
+        assert_eq!(frame.img().get_pixel(width - 1, height - 1)[3], 0);
+        assert_eq!(frame.img().get_pixel(width / 2, 0)[3], 255);
+    }
+
+    #[test]
+    fn rounded_canvas_falls_back_to_matte_for_jpeg() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let config = FrameConfig::default()
+            .with_canvas_radius(Some(CornerRadius::from_str("5%").unwrap()))
+            .with_matte(Rgba([255, 0, 0, 255]));
+        let file_pair = FilePair::new(
+            PathBuf::from("images/test/test_01.jpg"),
+            temp_dir.path().join("test_01.jpg"),
+        );
+
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        assert_eq!(frame.img().color(), image::ColorType::Rgb8);
+        assert_eq!(frame.img().get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn transparent_pixels_are_flattened_onto_the_frame() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("logo.png");
+        RgbaImage::from_fn(20, 20, |x, _| {
+            Rgba([255, 0, 0, if x < 10 { 0 } else { 255 }])
+        })
+        .save(&input)
+        .unwrap();
+        let config = FrameConfig::default().with_background(Background::Color);
+        let file_pair = FilePair::new(input.clone(), temp_dir.path().join("out.png"));
+
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        let (x, y) = (frame.position().x(), frame.position().y());
+        assert_eq!(
+            frame.img().get_pixel(x + 2, y + 2),
+            Rgba([255, 255, 255, 255])
+        );
+        assert_eq!(frame.img().get_pixel(x + 15, y + 2), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn transparent_pixels_are_kept() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("logo.png");
+        RgbaImage::from_fn(20, 20, |x, _| {
+            Rgba([255, 0, 0, if x < 10 { 0 } else { 128 }])
+        })
+        .save(&input)
+        .unwrap();
+        let config = FrameConfig::default().with_transparency(Transparency::Keep);
+        let file_pair = FilePair::new(input.clone(), temp_dir.path().join("out.png"));
+
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        let (x, y) = (frame.position().x(), frame.position().y());
+        assert_eq!(frame.img().get_pixel(x + 2, y + 2)[3], 0);
+        assert_eq!(frame.img().get_pixel(x + 15, y + 2), Rgba([255, 0, 0, 128]));
+        assert_eq!(frame.img().get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn frame_colors_match_the_image_color_space() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("p3.png");
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+        let red = Rgba([255, 0, 0, 255]);
+        let orange = Rgba([240, 120, 20, 255]);
+        save_png_with_icc_profile(&input, &RgbaImage::from_pixel(20, 20, orange), p3.clone());
+        let color_space = ColorSpace::from_icc(&p3).unwrap();
+        let config = FrameConfig::new(
+            None,
+            Fill::Solid(red),
+            None,
+            Margins::default(),
+            RelativePosition::default(),
+        );
+
+        // The profile is kept, so the frame is converted into the image's color space.
+        let file_pair = FilePair::new(input.clone(), temp_dir.path().join("out.png"));
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        let (x, y) = (frame.position().x(), frame.position().y());
+        assert_eq!(
+            frame.img().get_pixel(0, 0),
+            color_space.color_from_srgb(red)
+        );
+        assert_eq!(frame.img().get_pixel(x, y), orange);
+
+        // The profile is left out, so the image is converted to sRGB instead.
+        let config = config.with_metadata(KeepMetadata::from_str("none").unwrap());
+        let file_pair = FilePair::new(input, temp_dir.path().join("out.png"));
+        let frame = Frame::build(file_pair, &config).unwrap().draw(&config);
+        assert_eq!(frame.img().get_pixel(0, 0), red);
+        assert_ne!(frame.img().get_pixel(x, y), orange);
+    }
+
+    #[test]
+    fn non_rgb_profiles_are_dropped() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("gray.png");
+        let output = temp_dir.path().join("out.png");
+        let gray = moxcms::ColorProfile::new_gray_with_gamma(2.2)
+            .encode()
+            .unwrap();
+        let pixels = RgbaImage::from_pixel(20, 20, Rgba([128, 128, 128, 255]));
+        save_png_with_icc_profile(&input, &pixels, gray);
+        let config = FrameConfig::default();
+
+        let frame = Frame::build(FilePair::new(input, output.clone()), &config).unwrap();
+        assert!(frame.metadata().icc(config.metadata()).is_none());
+        frame.draw(&config).save(&config, false).unwrap();
+        let mut decoder = ImageReader::open(&output).unwrap().into_decoder().unwrap();
+        assert!(decoder.icc_profile().unwrap().is_none());
+    }
+
+    #[test]
+    fn corner_edges_are_blended_in_linear_light() {
+        let temp_dir = TempDir::with_prefix(TEST_FS_PREFIX).unwrap();
+        let input = temp_dir.path().join("white.png");
+        RgbaImage::from_pixel(40, 40, Rgba([255, 255, 255, 255]))
+            .save(&input)
+            .unwrap();
+        let config = FrameConfig::new(
+            None,
+            Fill::Solid(Rgba([0, 0, 0, 255])),
+            Some(CornerRadius::from_str("10").unwrap()),
+            Margins::default(),
+            RelativePosition::default(),
+        )
+        .with_blending(Blending::Linear);
+        let file_pair = FilePair::new(input, temp_dir.path().join("out.png"));
+
+        let frame = Frame::build(file_pair, &config).unwrap();
+        let window = frame.window().clone();
+        let frame = frame.draw(&config);
+        let (left, top, right, bottom) = window.bounds(u32::MAX, u32::MAX);
+        let mut edges = 0;
+        for y in top..bottom {
+            for x in left..right {
+                let coverage = window.coverage(x, y);
+                // White over black lets through as much light as the pixel's coverage.
+                let expected = (composite::linear_to_srgb(coverage) * 255.).round() as u8;
+                assert_eq!(frame.img().get_pixel(x, y)[0], expected, "({x}, {y})");
+                if coverage > 0. && coverage < 1. {
+                    edges += 1;
+                }
+            }
+        }
+        assert!(edges > 0);
     }
 
     #[test]
@@ -0,0 +1,160 @@
+//! The outline module describes the shape of the image window and of the shapes drawn around it.
+use crate::frame::pixel_source::PixelSource;
+use crate::frame::{Antialias, Mask};
+use crate::geometry::{Border, Point, Radii, Size};
+use derive_getters::Getters;
+
+/// The shape of the image window, or of a shape drawn at a given distance around it.
+#[derive(Clone, Debug, Getters)]
+pub struct Outline {
+    /// The top-left corner of the bounding rectangle.
+    position: Point,
+    /// The size of the bounding rectangle.
+    size: Size,
+    /// The radii of the rectangle's corners.
+    radii: Radii,
+    /// The shape cut out of the rectangle.
+    mask: Mask,
+    /// How the coverage of pixels along curved edges is measured.
+    antialias: Antialias,
+}
+
+impl Outline {
+    /// Create a new outline.
+    pub fn new(
+        position: Point,
+        size: Size,
+        radii: Radii,
+        mask: Mask,
+        antialias: Antialias,
+    ) -> Self {
+        Self {
+            position,
+            size,
+            radii,
+            mask,
+            antialias,
+        }
+    }
+
+    /// Create the outline at the given distance from each side of this one.
+    ///
+    /// Rounded corners grow with the outline, so they stay concentric with this outline's corners,
+    /// and masks are fitted to the larger rectangle.
+    pub fn around(&self, distance: &Border) -> Self {
+        let grow = distance
+            .top()
+            .min(distance.right())
+            .min(distance.bottom())
+            .min(distance.left());
+        Self {
+            position: Point::new(
+                self.position.x().saturating_sub(distance.left()),
+                self.position.y().saturating_sub(distance.top()),
+            ),
+            size: Size::from((
+                self.size.width() + distance.left() + distance.right(),
+                self.size.height() + distance.top() + distance.bottom(),
+            )),
+            radii: self.radii.grow(grow),
+            mask: self.mask.clone(),
+            antialias: self.antialias,
+        }
+    }
+
+    /// The same outline with its top-left corner moved to the given position.
+    pub fn moved_to(&self, position: Point) -> Self {
+        Self {
+            position,
+            ..self.clone()
+        }
+    }
+
+    /// Determine the source of the given pixel: inside, outside or on the edge of the outline.
+    pub fn source(&self, x: u32, y: u32) -> PixelSource {
+        let pixel = Point::new(x, y);
+        let inside = x >= self.position.x()
+            && y >= self.position.y()
+            && x < self.position.x() + self.size.width()
+            && y < self.position.y() + self.size.height();
+        if !inside {
+            return PixelSource::Background;
+        }
+
+        match self.mask.coverage(
+            x - self.position.x(),
+            y - self.position.y(),
+            &self.size,
+            self.antialias,
+        ) {
+            Some(coverage) => PixelSource::from_coverage(coverage),
+            None => PixelSource::at(
+                pixel,
+                &self.position,
+                &self.size,
+                &self.radii,
+                self.antialias,
+            ),
+        }
+    }
+
+    /// The fraction (0 to 1) of the given pixel covered by the outline.
+    pub fn coverage(&self, x: u32, y: u32) -> f32 {
+        self.source(x, y).coverage()
+    }
+
+    /// The pixel bounds of the outline, clipped to a canvas of the given size, as (left, top, right, bottom).
+    pub fn bounds(&self, canvas_width: u32, canvas_height: u32) -> (u32, u32, u32, u32) {
+        (
+            self.position.x(),
+            self.position.y(),
+            (self.position.x() + self.size.width()).min(canvas_width),
+            (self.position.y() + self.size.height()).min(canvas_height),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outline_around_image() {
+        let window = Outline::new(
+            Point::new(10, 10),
+            Size::new(20, 20),
+            Radii::uniform(4),
+            Mask::default(),
+            Antialias::default(),
+        );
+        let outline = window.around(&Border::new(1, 2, 3, 4));
+        assert_eq!(outline.bounds(100, 100), (6, 9, 32, 33));
+        assert_eq!(outline.bounds(30, 30), (6, 9, 30, 30));
+        assert_eq!(outline.radii, Radii::uniform(5));
+        assert_eq!(outline.coverage(6, 20), 1.);
+        assert_eq!(outline.coverage(5, 20), 0.);
+        assert_eq!(outline.coverage(6, 9), 0.);
+    }
+
+    #[test]
+    fn masked_outline() {
+        let window = Outline::new(
+            Point::new(10, 10),
+            Size::new(20, 20),
+            Radii::default(),
+            Mask::Circle,
+            Antialias::default(),
+        );
+        assert_eq!(window.source(20, 20), PixelSource::Image);
+        assert_eq!(window.source(10, 10), PixelSource::Background);
+        assert_eq!(window.source(5, 20), PixelSource::Background);
+
+        // The circle grows with the outline around it.
+        let outline = window.around(&Border::new(5, 5, 5, 5));
+        assert_eq!(outline.source(6, 20), PixelSource::Image);
+        assert_eq!(outline.source(6, 6), PixelSource::Background);
+
+        let moved = window.moved_to(Point::new(0, 0));
+        assert_eq!(moved.source(10, 10), PixelSource::Image);
+    }
+}
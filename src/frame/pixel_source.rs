@@ -1,6 +1,7 @@
 //! The Pixel Source module is responsible
 //! for determining the source of a pixel during the frame drawing process.
-use crate::geometry::{Point, Size};
+use crate::frame::Antialias;
+use crate::geometry::{Point, Radii, Size};
 
 /// The source of a pixel during the frame drawing process.
 #[derive(Debug, PartialEq)]
@@ -14,51 +15,76 @@ pub enum PixelSource {
 }
 
 impl PixelSource {
-    /// Determine the source of a pixel based on its position and the image's corner radius.
+    /// Determine the source of a pixel based on its position and the radii of the image's corners,
+    /// measuring the coverage of pixels along rounded corners with the given anti-aliasing.
     pub fn at(
         pixel: Point,
         img_position: &Point,
         img_size: &Size,
-        corner_radius: &Option<u32>,
+        radii: &Radii,
+        antialias: Antialias,
     ) -> Self {
         if is_border_pixel(&pixel, img_position, img_size) {
             return PixelSource::Background;
-        } else if corner_radius.is_none() {
+        } else if radii.is_square() {
             return PixelSource::Image;
         }
 
-        let radius = corner_radius.unwrap() as i32;
         let x = (pixel.x() - img_position.x()) as i32;
         let y = (pixel.y() - img_position.y()) as i32;
         let width = img_size.width() as i32;
         let height = img_size.height() as i32;
+        let top_left = radii.top_left() as i32;
+        let top_right = radii.top_right() as i32;
+        let bottom_right = radii.bottom_right() as i32;
+        let bottom_left = radii.bottom_left() as i32;
 
-        let (dx, dy) = if x < radius && y < radius {
+        // The distances from the center of the corner's circle to the nearest edges of the pixel.
+        let (u, v, radius) = if x < top_left && y < top_left {
             // Top Left
-            (radius - x, radius - y)
-        } else if x < radius && y >= height - radius {
+            (top_left - x - 1, top_left - y - 1, top_left)
+        } else if x < bottom_left && y >= height - bottom_left {
             // Bottom Left
-            (radius - x, y - height + radius + 1)
-        } else if x >= width - radius && y < radius {
+            (bottom_left - x - 1, y - height + bottom_left, bottom_left)
+        } else if x >= width - top_right && y < top_right {
             // Top Right
-            (x - width + radius + 1, radius - y)
-        } else if x >= width - radius && y >= height - radius {
+            (x - width + top_right, top_right - y - 1, top_right)
+        } else if x >= width - bottom_right && y >= height - bottom_right {
             // Bottom Right
-            (x - width + radius + 1, y - height + radius + 1)
+            (
+                x - width + bottom_right,
+                y - height + bottom_right,
+                bottom_right,
+            )
         } else {
             return PixelSource::Image;
         };
 
-        let distance_squared = dx * dx + dy * dy;
-        let radius_squared = radius * radius;
-        if distance_squared > radius_squared {
-            PixelSource::Background
-        } else if distance_squared <= radius_squared - radius {
+        PixelSource::from_coverage(antialias.corner_coverage(
+            *radii.shape(),
+            u as f32,
+            v as f32,
+            radius as f32,
+        ))
+    }
+
+    /// The source of a pixel covered by the image in the given fraction (0 to 1).
+    pub fn from_coverage(coverage: f32) -> Self {
+        if coverage >= 1. {
             PixelSource::Image
+        } else if coverage <= 0. {
+            PixelSource::Background
         } else {
-            let distance = (distance_squared as f32).sqrt();
-            let blend = 1.0 - (distance / radius as f32);
-            PixelSource::Blend(blend)
+            PixelSource::Blend(coverage)
+        }
+    }
+
+    /// The fraction (0 to 1) of the pixel covered by the image.
+    pub fn coverage(&self) -> f32 {
+        match self {
+            PixelSource::Image => 1.,
+            PixelSource::Blend(alpha) => *alpha,
+            PixelSource::Background => 0.,
         }
     }
 }
@@ -77,16 +103,23 @@ fn is_border_pixel(pixel: &Point, img_position: &Point, img_size: &Size) -> bool
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::geometry::CornerShape;
 
     #[test]
     fn image_pixels() {
         let img_position = Point::new(10, 10);
         let img_size = Size::new(100, 100);
-        let corner_radius = Some(10);
+        let corner_radius = Radii::uniform(10);
 
         let mid_image_pixel = Point::new(60, 60);
         assert_eq!(
-            PixelSource::at(mid_image_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                mid_image_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Image
         );
 
@@ -96,7 +129,8 @@ mod tests {
                 top_left_in_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Image
         );
@@ -107,7 +141,8 @@ mod tests {
                 top_right_in_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Image
         );
@@ -118,7 +153,8 @@ mod tests {
                 bottom_left_in_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Image
         );
@@ -129,34 +165,59 @@ mod tests {
                 bottom_right_in_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Image
         );
 
-        let corner_radius = None;
+        let corner_radius = Radii::default();
 
         let top_left_pixel = Point::new(10, 10);
         assert_eq!(
-            PixelSource::at(top_left_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                top_left_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Image
         );
 
         let top_right_pixel = Point::new(109, 10);
         assert_eq!(
-            PixelSource::at(top_right_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                top_right_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Image
         );
 
         let bottom_left_pixel = Point::new(10, 109);
         assert_eq!(
-            PixelSource::at(bottom_left_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                bottom_left_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Image
         );
 
         let bottom_right_pixel = Point::new(109, 109);
         assert_eq!(
-            PixelSource::at(bottom_right_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                bottom_right_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Image
         );
     }
@@ -165,23 +226,41 @@ mod tests {
     fn border_pixels() {
         let img_position = Point::new(10, 10);
         let img_size = Size::new(100, 100);
-        let corner_radius = Some(10);
+        let corner_radius = Radii::uniform(10);
 
         let left_border_pixel = Point::new(5, 60);
         assert_eq!(
-            PixelSource::at(left_border_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                left_border_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Background
         );
 
         let right_border_pixel = Point::new(115, 60);
         assert_eq!(
-            PixelSource::at(right_border_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                right_border_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Background
         );
 
         let top_border_pixel = Point::new(60, 5);
         assert_eq!(
-            PixelSource::at(top_border_pixel, &img_position, &img_size, &corner_radius),
+            PixelSource::at(
+                top_border_pixel,
+                &img_position,
+                &img_size,
+                &corner_radius,
+                Antialias::default()
+            ),
             PixelSource::Background
         );
 
@@ -191,7 +270,8 @@ mod tests {
                 bottom_border_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -202,7 +282,8 @@ mod tests {
                 top_left_corner_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -213,7 +294,8 @@ mod tests {
                 top_right_corner_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -224,7 +306,8 @@ mod tests {
                 bottom_left_corner_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -235,40 +318,44 @@ mod tests {
                 bottom_right_corner_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
 
-        let top_left_outside_corner_radius_pixel = Point::new(12, 12);
+        let top_left_outside_corner_radius_pixel = Point::new(11, 11);
         assert_eq!(
             PixelSource::at(
                 top_left_outside_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
 
-        let top_right_outside_corner_radius_pixel = Point::new(108, 12);
+        let top_right_outside_corner_radius_pixel = Point::new(108, 11);
         assert_eq!(
             PixelSource::at(
                 top_right_outside_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
 
-        let bottom_left_outside_corner_radius_pixel = Point::new(12, 108);
+        let bottom_left_outside_corner_radius_pixel = Point::new(11, 108);
         assert_eq!(
             PixelSource::at(
                 bottom_left_outside_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -279,7 +366,8 @@ mod tests {
                 bottom_right_outside_corner_radius_pixel,
                 &img_position,
                 &img_size,
-                &corner_radius
+                &corner_radius,
+                Antialias::default()
             ),
             PixelSource::Background
         );
@@ -289,66 +377,217 @@ mod tests {
     fn blended_pixels() {
         let img_position = Point::new(10, 10);
         let img_size = Size::new(100, 100);
-        let corner_radius = Some(10);
+        let corner_radius = Radii::uniform(10);
 
-        let top_left_corner_pixel = Point::new(13, 13);
+        let top_left_corner_pixel = Point::new(12, 13);
         let pixel_source = PixelSource::at(
             top_left_corner_pixel,
             &img_position,
             &img_size,
             &corner_radius,
+            Antialias::default(),
         );
         match pixel_source {
             PixelSource::Blend(blend) => {
-                assert!(blend > 0.01);
-                assert!(blend < 0.02);
+                assert!(blend > 0.5);
+                assert!(blend < 0.7);
             }
             _ => panic!("Expected PixelSource::Blend"),
         }
 
-        let top_right_corner_pixel = Point::new(106, 13);
+        let top_right_corner_pixel = Point::new(107, 13);
         let pixel_source = PixelSource::at(
             top_right_corner_pixel,
             &img_position,
             &img_size,
             &corner_radius,
+            Antialias::default(),
         );
         match pixel_source {
             PixelSource::Blend(blend) => {
-                assert!(blend > 0.01);
-                assert!(blend < 0.02);
+                assert!(blend > 0.5);
+                assert!(blend < 0.7);
             }
             _ => panic!("Expected PixelSource::Blend"),
         }
 
-        let bottom_left_corner_pixel = Point::new(13, 106);
+        let bottom_left_corner_pixel = Point::new(12, 106);
         let pixel_source = PixelSource::at(
             bottom_left_corner_pixel,
             &img_position,
             &img_size,
             &corner_radius,
+            Antialias::default(),
         );
         match pixel_source {
             PixelSource::Blend(blend) => {
-                assert!(blend > 0.01);
-                assert!(blend < 0.02);
+                assert!(blend > 0.5);
+                assert!(blend < 0.7);
             }
             _ => panic!("Expected PixelSource::Blend"),
         }
 
-        let bottom_right_corner_pixel = Point::new(106, 106);
+        let bottom_right_corner_pixel = Point::new(107, 106);
         let pixel_source = PixelSource::at(
             bottom_right_corner_pixel,
             &img_position,
             &img_size,
             &corner_radius,
+            Antialias::default(),
         );
         match pixel_source {
             PixelSource::Blend(blend) => {
-                assert!(blend > 0.01);
-                assert!(blend < 0.02);
+                assert!(blend > 0.5);
+                assert!(blend < 0.7);
             }
             _ => panic!("Expected PixelSource::Blend"),
         }
     }
+
+    #[test]
+    fn per_corner_radii() {
+        let img_position = Point::new(10, 10);
+        let img_size = Size::new(100, 100);
+        let radii = Radii::new(20, 0, 10, 0);
+
+        // The rounded top-left corner excludes a pixel that the square top-right corner keeps.
+        assert_eq!(
+            PixelSource::at(
+                Point::new(12, 12),
+                &img_position,
+                &img_size,
+                &radii,
+                Antialias::default()
+            ),
+            PixelSource::Background
+        );
+        assert_eq!(
+            PixelSource::at(
+                Point::new(107, 12),
+                &img_position,
+                &img_size,
+                &radii,
+                Antialias::default()
+            ),
+            PixelSource::Image
+        );
+
+        // The smaller bottom-right radius excludes less than the top-left radius.
+        assert_eq!(
+            PixelSource::at(
+                Point::new(104, 104),
+                &img_position,
+                &img_size,
+                &radii,
+                Antialias::default()
+            ),
+            PixelSource::Image
+        );
+        assert_eq!(
+            PixelSource::at(
+                Point::new(14, 14),
+                &img_position,
+                &img_size,
+                &radii,
+                Antialias::default()
+            ),
+            PixelSource::Background
+        );
+        assert_eq!(
+            PixelSource::at(
+                Point::new(12, 107),
+                &img_position,
+                &img_size,
+                &radii,
+                Antialias::default()
+            ),
+            PixelSource::Image
+        );
+    }
+
+    #[test]
+    fn edge_coverage_at_several_radii() {
+        let img_position = Point::new(0, 0);
+        let img_size = Size::new(200, 200);
+
+        for radius in [4, 10, 50] {
+            let radii = Radii::uniform(radius);
+            // The pixel just inside the straight edge next to the corner is fully covered...
+            assert_eq!(
+                PixelSource::at(
+                    Point::new(radius, 0),
+                    &img_position,
+                    &img_size,
+                    &radii,
+                    Antialias::default()
+                ),
+                PixelSource::Image
+            );
+            // ...while the pixel at the very corner is fully outside the curve.
+            assert_eq!(
+                PixelSource::at(
+                    Point::new(0, 0),
+                    &img_position,
+                    &img_size,
+                    &radii,
+                    Antialias::default()
+                ),
+                PixelSource::Background
+            );
+            // Along the curve, coverage fades in from outside to inside.
+            let coverages: Vec<f32> = (0..radius)
+                .map(|i| {
+                    PixelSource::at(
+                        Point::new(i, i),
+                        &img_position,
+                        &img_size,
+                        &radii,
+                        Antialias::default(),
+                    )
+                    .coverage()
+                })
+                .collect();
+            assert!(coverages.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(coverages.iter().any(|c| *c > 0. && *c < 1.));
+        }
+    }
+
+    #[test]
+    fn shaped_corners() {
+        let img_position = Point::new(10, 10);
+        let img_size = Size::new(100, 100);
+        let pixel_source = |shape: CornerShape, x: u32, y: u32| {
+            PixelSource::at(
+                Point::new(x, y),
+                &img_position,
+                &img_size,
+                &Radii::uniform(10).with_shape(shape),
+                Antialias::default(),
+            )
+        };
+
+        // The chamfer cuts off a pixel the circle keeps whole.
+        assert_eq!(
+            pixel_source(CornerShape::Circle, 14, 14),
+            PixelSource::Image
+        );
+        assert_eq!(
+            pixel_source(CornerShape::Chamfer, 14, 14),
+            PixelSource::Background
+        );
+        // The squircle keeps a pixel the circle partly cuts off.
+        assert!(matches!(
+            pixel_source(CornerShape::Circle, 12, 13),
+            PixelSource::Blend(_)
+        ));
+        assert_eq!(
+            pixel_source(CornerShape::Squircle(5.), 12, 13),
+            PixelSource::Image
+        );
+        // Pixels along the chamfer's diagonal are half covered.
+        match pixel_source(CornerShape::Chamfer, 15, 14) {
+            PixelSource::Blend(blend) => assert!((blend - 0.5).abs() < 0.01),
+            other => panic!("Expected PixelSource::Blend, got {other:?}"),
+        }
+    }
 }
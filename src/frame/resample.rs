@@ -0,0 +1,49 @@
+//! The resample module is responsible for choosing how images are resized to fit an exact output size.
+use image::imageops::FilterType;
+use strum_macros::{AsRefStr, EnumString};
+
+/// The filter used to resample the image when it is resized to fit an exact output size.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Resampling {
+    /// Each pixel takes the value of the nearest source pixel, keeping hard edges.
+    Nearest,
+    /// Pixels are interpolated linearly between their neighbors.
+    Linear,
+    /// Pixels are interpolated with a Catmull-Rom cubic curve.
+    Cubic,
+    /// Pixels are averaged with a Gaussian weight, softening the image.
+    Gaussian,
+    /// Pixels are interpolated with a Lanczos window of 3, the sharpest of the filters.
+    #[default]
+    Lanczos,
+}
+
+impl Resampling {
+    /// The image filter matching the resampling method.
+    pub fn filter(&self) -> FilterType {
+        match self {
+            Resampling::Nearest => FilterType::Nearest,
+            Resampling::Linear => FilterType::Triangle,
+            Resampling::Cubic => FilterType::CatmullRom,
+            Resampling::Gaussian => FilterType::Gaussian,
+            Resampling::Lanczos => FilterType::Lanczos3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_resampling() {
+        assert_eq!(Resampling::from_str("Cubic").unwrap(), Resampling::Cubic);
+        assert_eq!(
+            Resampling::from_str("lanczos").unwrap().filter(),
+            FilterType::Lanczos3
+        );
+        assert!(Resampling::from_str("bilinear").is_err());
+    }
+}
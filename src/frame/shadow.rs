@@ -0,0 +1,237 @@
+//! The shadow module is responsible for drawing a drop shadow behind the image.
+use crate::errors::OliframeError;
+use crate::frame::composite::{self, Blending};
+use crate::frame::outline::Outline;
+use crate::geometry::{Border, Point, percentage};
+use derive_getters::Getters;
+use image::imageops;
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+use std::str::FromStr;
+
+/// A drop shadow cast by the image onto the frame.
+///
+/// The user can provide the shadow geometry in pixels, in the same order as CSS `box-shadow`:
+/// - `8 8`: horizontal and vertical offsets
+/// - `8 8 16`: offsets and blur radius
+/// - `8 8 16 4`: offsets, blur radius and spread
+///
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct Shadow {
+    /// The horizontal offset of the shadow (positive values move it right).
+    offset_x: i32,
+    /// The vertical offset of the shadow (positive values move it down).
+    offset_y: i32,
+    /// The blur radius of the shadow.
+    blur: u32,
+    /// The distance by which the shadow is grown beyond the image on every side.
+    spread: u32,
+    /// The color of the shadow.
+    color: Rgba<u8>,
+    /// The opacity of the shadow, from 0 to 1.
+    opacity: f32,
+}
+
+impl Shadow {
+    /// Set the color of the shadow, and its opacity as a percentage.
+    pub fn with_color(mut self, color: Rgba<u8>, opacity: f32) -> Result<Self, OliframeError> {
+        self.color = color;
+        self.opacity = percentage(opacity, "Shadow opacity")?;
+        Ok(self)
+    }
+
+    /// The space needed on each side of the image to draw the shadow without clipping it.
+    pub fn extent(&self) -> Border {
+        let reach = (self.spread + self.blur) as i32;
+        let side = |offset: i32| (reach + offset).max(0) as u32;
+        Border::new(
+            side(-self.offset_y),
+            side(self.offset_x),
+            side(self.offset_y),
+            side(-self.offset_x),
+        )
+    }
+
+    /// Paint the shadow of the image window onto the canvas.
+    pub fn paint(&self, canvas: &mut RgbaImage, window: &Outline, blending: Blending) {
+        let mask = self.mask(window);
+        let pad = self.padding() as i64;
+        let left = window.position().x() as i64 + self.offset_x as i64 - self.spread as i64 - pad;
+        let top = window.position().y() as i64 + self.offset_y as i64 - self.spread as i64 - pad;
+        let (width, height) = canvas.dimensions();
+
+        for (mx, my, coverage) in mask.enumerate_pixels() {
+            let (x, y) = (left + mx as i64, top + my as i64);
+            if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                continue;
+            }
+            let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+            composite::over(
+                pixel,
+                &self.color,
+                coverage[0] as f32 / 255. * self.opacity,
+                blending,
+            );
+        }
+    }
+
+    /// The blank space kept around the shadow shape in its mask, so the blur is not cut off.
+    fn padding(&self) -> u32 {
+        self.blur * 2
+    }
+
+    /// Build the blurred coverage mask of the shadow.
+    fn mask(&self, window: &Outline) -> GrayImage {
+        let pad = self.padding();
+        let spread = Border::new(self.spread, self.spread, self.spread, self.spread);
+        let shape = window.around(&spread).moved_to(Point::new(pad, pad));
+
+        let mask = GrayImage::from_fn(
+            shape.size().width() + pad * 2,
+            shape.size().height() + pad * 2,
+            |x, y| Luma([(shape.coverage(x, y) * 255.).round() as u8]),
+        );
+
+        if self.blur == 0 {
+            mask
+        } else {
+            imageops::fast_blur(&mask, self.blur as f32 / 2.)
+        }
+    }
+}
+
+impl FromStr for Shadow {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let values = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                v.strip_suffix("px")
+                    .unwrap_or(v)
+                    .parse::<i32>()
+                    .map_err(|_| OliframeError::InvalidInput(format!("Invalid shadow value: {v}")))
+            })
+            .collect::<Result<Vec<i32>, OliframeError>>()?;
+
+        if !(2..=4).contains(&values.len()) {
+            return Err(OliframeError::InvalidInput(format!(
+                "Shadows must be specified with 2-4 values (received {} values).",
+                values.len()
+            )));
+        }
+
+        let blur = *values.get(2).unwrap_or(&0);
+        let spread = *values.get(3).unwrap_or(&0);
+        if blur < 0 || spread < 0 {
+            return Err(OliframeError::InvalidInput(
+                "Shadow blur and spread cannot be negative.".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            offset_x: values[0],
+            offset_y: values[1],
+            blur: blur as u32,
+            spread: spread as u32,
+            color: Rgba([0, 0, 0, 255]),
+            opacity: 0.5,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Radii, Size};
+    use crate::test_utils::window;
+
+    #[test]
+    fn parse_shadow() {
+        let shadow = "8 -4".parse::<Shadow>().unwrap();
+        assert_eq!(shadow.offset_x(), 8);
+        assert_eq!(shadow.offset_y(), -4);
+        assert_eq!(shadow.blur(), 0);
+        assert_eq!(shadow.spread(), 0);
+
+        let shadow = "8px,8px,16px,4px".parse::<Shadow>().unwrap();
+        assert_eq!(shadow.blur(), 16);
+        assert_eq!(shadow.spread(), 4);
+    }
+
+    #[test]
+    fn invalid_shadows_are_err() {
+        assert!("8".parse::<Shadow>().is_err());
+        assert!("8 8 8 8 8".parse::<Shadow>().is_err());
+        assert!("8 8 -8".parse::<Shadow>().is_err());
+        assert!("8 eight".parse::<Shadow>().is_err());
+        assert!("8pxpx 8".parse::<Shadow>().is_err());
+    }
+
+    #[test]
+    fn invalid_opacity_is_err() {
+        let shadow = "8 8".parse::<Shadow>().unwrap();
+        assert!(shadow.clone().with_color(Rgba([0, 0, 0, 255]), 50.).is_ok());
+        assert!(
+            shadow
+                .clone()
+                .with_color(Rgba([0, 0, 0, 255]), 150.)
+                .is_err()
+        );
+        // Small values are percentages too, not fractions.
+        let shadow = shadow.with_color(Rgba([0, 0, 0, 255]), 1.).unwrap();
+        assert_eq!(shadow.opacity(), 0.01);
+    }
+
+    #[test]
+    fn shadow_extent() {
+        let shadow = "10 -5 4 2".parse::<Shadow>().unwrap();
+        let extent = shadow.extent();
+        assert_eq!(extent.top(), 11);
+        assert_eq!(extent.right(), 16);
+        assert_eq!(extent.bottom(), 1);
+        assert_eq!(extent.left(), 0);
+    }
+
+    #[test]
+    fn paint_hard_shadow() {
+        let shadow = "5 5"
+            .parse::<Shadow>()
+            .unwrap()
+            .with_color(Rgba([0, 0, 0, 255]), 100.)
+            .unwrap();
+        let mut canvas = RgbaImage::from_pixel(30, 30, Rgba([255, 255, 255, 255]));
+        shadow.paint(
+            &mut canvas,
+            &window(Point::new(5, 5), Size::new(10, 10), Radii::default()),
+            Blending::Srgb,
+        );
+
+        assert_eq!(canvas.get_pixel(12, 12), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(19, 19), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(20, 20), &Rgba([255, 255, 255, 255]));
+        assert_eq!(canvas.get_pixel(9, 9), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn paint_soft_shadow_follows_corner_radius() {
+        let shadow = "0 0 4"
+            .parse::<Shadow>()
+            .unwrap()
+            .with_color(Rgba([0, 0, 0, 255]), 100.)
+            .unwrap();
+        let mut canvas = RgbaImage::from_pixel(40, 40, Rgba([255, 255, 255, 255]));
+        shadow.paint(
+            &mut canvas,
+            &window(Point::new(10, 10), Size::new(20, 20), Radii::uniform(8)),
+            Blending::Srgb,
+        );
+
+        let center = canvas.get_pixel(20, 20)[0];
+        let edge = canvas.get_pixel(20, 9)[0];
+        let corner = canvas.get_pixel(10, 10)[0];
+        assert_eq!(center, 0);
+        assert!(edge > center && edge < 255);
+        assert!(corner > edge);
+    }
+}
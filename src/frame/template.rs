@@ -0,0 +1,221 @@
+//! The template module is responsible for filling text with values from each image's metadata.
+use crate::errors::OliframeError;
+use crate::frame::metadata::{FIELDS, Metadata};
+use std::str::FromStr;
+
+/// Text filled with values from each image's metadata.
+///
+/// The user can provide the template in the following format:
+/// - `{model}`: the value of a field, or nothing when the image does not have it
+/// - `{lens|model}`: the first of the given fields that the image has
+/// - `{lens|"unknown lens"}`: a quoted fallback used when none of the fields are found
+/// - `[ · ISO {iso}]`: an optional section, left out when any of its fields are missing
+/// - `{{`, `}}`, `[[` and `]]`: literal braces and brackets
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    /// The literal text and fields making up the template.
+    parts: Vec<Part>,
+}
+
+/// A piece of a template.
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    /// Literal text.
+    Text(String),
+    /// The first of the named fields found, or else the fallback text.
+    Field(Vec<String>, Option<String>),
+    /// A section left out when any of its fields are missing.
+    Optional(Vec<Part>),
+}
+
+impl Template {
+    /// Fill the template with the values from the given metadata.
+    pub fn render(&self, metadata: &Metadata) -> String {
+        render(&self.parts, metadata).0
+    }
+}
+
+/// Render the parts, along with whether every field was found or had a fallback.
+fn render(parts: &[Part], metadata: &Metadata) -> (String, bool) {
+    let mut text = String::new();
+    let mut complete = true;
+    for part in parts {
+        match part {
+            Part::Text(literal) => text.push_str(literal),
+            Part::Field(names, fallback) => {
+                match names
+                    .iter()
+                    .find_map(|name| metadata.get(name))
+                    .or(fallback.as_deref())
+                {
+                    Some(value) => text.push_str(value),
+                    None => complete = false,
+                }
+            }
+            Part::Optional(parts) => {
+                if let (section, true) = render(parts, metadata) {
+                    text.push_str(&section);
+                }
+            }
+        }
+    }
+    (text, complete)
+}
+
+impl FromStr for Template {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut chars = input.chars().peekable();
+        // The parts of the template, and of each optional section being parsed.
+        let mut stack: Vec<Vec<Part>> = vec![Vec::new()];
+        let mut text = String::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' | '[' | ']' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    text.push(c);
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => {
+                                return Err(OliframeError::InvalidInput(format!(
+                                    "Unclosed \"{{\" in template: {input}"
+                                )));
+                            }
+                        }
+                    }
+                    flush(&mut text, &mut stack);
+                    push(&mut stack, parse_field(&field, input)?);
+                }
+                '[' => {
+                    flush(&mut text, &mut stack);
+                    stack.push(Vec::new());
+                }
+                ']' if stack.len() > 1 => {
+                    flush(&mut text, &mut stack);
+                    let section = stack.pop().unwrap_or_default();
+                    push(&mut stack, Part::Optional(section));
+                }
+                '}' | ']' => {
+                    return Err(OliframeError::InvalidInput(format!(
+                        "Unmatched \"{c}\" in template: {input}"
+                    )));
+                }
+                _ => text.push(c),
+            }
+        }
+
+        if stack.len() > 1 {
+            return Err(OliframeError::InvalidInput(format!(
+                "Unclosed \"[\" in template: {input}"
+            )));
+        }
+        flush(&mut text, &mut stack);
+        Ok(Self {
+            parts: stack.pop().unwrap_or_default(),
+        })
+    }
+}
+
+/// Add the literal text collected so far to the innermost section.
+fn flush(text: &mut String, stack: &mut [Vec<Part>]) {
+    if !text.is_empty() {
+        push(stack, Part::Text(std::mem::take(text)));
+    }
+}
+
+/// Add a part to the innermost section.
+fn push(stack: &mut [Vec<Part>], part: Part) {
+    if let Some(section) = stack.last_mut() {
+        section.push(part);
+    }
+}
+
+/// Parse the contents of a field, such as `lens|model|"unknown"`.
+fn parse_field(field: &str, input: &str) -> Result<Part, OliframeError> {
+    let mut names = Vec::new();
+    let mut fallback = None;
+    for alternative in field.split('|').map(str::trim) {
+        if fallback.is_some() {
+            return Err(OliframeError::InvalidInput(format!(
+                "The quoted fallback must come last in template: {input}"
+            )));
+        }
+        match alternative
+            .strip_prefix('"')
+            .and_then(|a| a.strip_suffix('"'))
+        {
+            Some(literal) => fallback = Some(literal.to_string()),
+            None if FIELDS.contains(&alternative.to_lowercase().as_str()) => {
+                names.push(alternative.to_lowercase())
+            }
+            None => {
+                return Err(OliframeError::InvalidInput(format!(
+                    "Unknown template field \"{alternative}\".  Available fields: {}",
+                    FIELDS.join(", ")
+                )));
+            }
+        }
+    }
+    Ok(Part::Field(names, fallback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        Metadata::from_fields(&[
+            ("make", "FUJIFILM"),
+            ("model", "X100V"),
+            ("fnumber", "5.6"),
+            ("exposure", "1/60"),
+            ("iso", "2500"),
+        ])
+    }
+
+    fn render(template: &str) -> String {
+        Template::from_str(template).unwrap().render(&metadata())
+    }
+
+    #[test]
+    fn fill_fields() {
+        assert_eq!(
+            render("{make} {model} · f/{fnumber} · {exposure}s · ISO {iso}"),
+            "FUJIFILM X100V · f/5.6 · 1/60s · ISO 2500"
+        );
+        assert_eq!(render("Plain text"), "Plain text");
+        assert_eq!(render("{{{model}}} [[1]]"), "{X100V} [1]");
+    }
+
+    #[test]
+    fn missing_fields_fall_back() {
+        assert_eq!(render("{model} {lens}"), "X100V ");
+        assert_eq!(render("{lens|model}"), "X100V");
+        assert_eq!(render("{lens|\"fixed lens\"}"), "fixed lens");
+        assert_eq!(
+            render("{model}[ · {lens}][ · ISO {iso}]"),
+            "X100V · ISO 2500"
+        );
+        assert_eq!(render("[{make} [{lens} ]{model}]"), "FUJIFILM X100V");
+        assert_eq!(render("[{lens|\"-\"}]"), "-");
+    }
+
+    #[test]
+    fn invalid_templates_are_err() {
+        assert!(Template::from_str("{shutter}").is_err());
+        assert!(Template::from_str("{\"a\"|model}").is_err());
+        assert!(Template::from_str("[{model}").is_err());
+        assert!(Template::from_str("{model}]").is_err());
+        assert!(Template::from_str("model}").is_err());
+        assert!(Template::from_str("{model").is_err());
+        assert!(Template::from_str("{}").is_err());
+    }
+}
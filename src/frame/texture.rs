@@ -0,0 +1,176 @@
+//! The texture module is responsible for filling the frame with an image file, such as a paper texture.
+use crate::errors::OliframeError;
+use crate::frame::build;
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use strum_macros::{AsRefStr, EnumString};
+
+/// An image used to fill the frame around the input image.
+///
+/// The texture is decoded once when the configuration is built,
+/// and shared by every image processed in the run.
+#[derive(Clone, PartialEq)]
+pub struct Texture {
+    /// The decoded texture image.
+    image: Arc<RgbaImage>,
+    /// How the texture is fitted to the frame.
+    fit: TextureFit,
+}
+
+/// How a texture is fitted to the frame.
+#[derive(AsRefStr, Clone, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum TextureFit {
+    /// The texture is repeated at its original size.
+    Tile,
+    /// The texture is scaled to the exact size of the frame, ignoring its aspect ratio.
+    Stretch,
+    /// The texture is scaled to cover the frame, cropping any overflow.
+    #[default]
+    Cover,
+    /// The texture is placed at its original size in the center of the frame.
+    Center,
+}
+
+impl Texture {
+    /// Load a texture from the given image file.
+    pub fn load(path: &PathBuf, fit: TextureFit) -> Result<Self, OliframeError> {
+        let (image, _, metadata) = build::load(path)?;
+        let mut image = image.to_rgba8();
+        if let Some(color_space) = metadata.color_space() {
+            color_space.image_to_srgb(&mut image);
+        }
+        Ok(Self::new(image, fit))
+    }
+
+    /// Create a new texture from an already decoded image.
+    pub fn new(image: RgbaImage, fit: TextureFit) -> Self {
+        Self {
+            image: Arc::new(image),
+            fit,
+        }
+    }
+
+    /// Paint the texture over the given canvas, which is the size of the output image.
+    pub fn paint(&self, canvas: &mut RgbaImage) {
+        let (width, height) = canvas.dimensions();
+        let (tex_width, tex_height) = self.image.dimensions();
+        if tex_width == 0 || tex_height == 0 {
+            return;
+        }
+
+        match self.fit {
+            TextureFit::Tile => {
+                let tiled = RgbaImage::from_fn(width, height, |x, y| {
+                    *self.image.get_pixel(x % tex_width, y % tex_height)
+                });
+                imageops::overlay(canvas, &tiled, 0, 0);
+            }
+            TextureFit::Stretch => {
+                let stretched = imageops::resize(&*self.image, width, height, FilterType::Triangle);
+                imageops::overlay(canvas, &stretched, 0, 0);
+            }
+            TextureFit::Cover => {
+                let scale =
+                    (width as f32 / tex_width as f32).max(height as f32 / tex_height as f32);
+                let scaled_width = ((tex_width as f32 * scale).round() as u32).max(width);
+                let scaled_height = ((tex_height as f32 * scale).round() as u32).max(height);
+                let scaled = imageops::resize(
+                    &*self.image,
+                    scaled_width,
+                    scaled_height,
+                    FilterType::Triangle,
+                );
+                let x = (width as i64 - scaled_width as i64) / 2;
+                let y = (height as i64 - scaled_height as i64) / 2;
+                imageops::overlay(canvas, &scaled, x, y);
+            }
+            TextureFit::Center => {
+                let x = (width as i64 - tex_width as i64) / 2;
+                let y = (height as i64 - tex_height as i64) / 2;
+                imageops::overlay(canvas, &*self.image, x, y);
+            }
+        }
+    }
+}
+
+impl Debug for Texture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Texture")
+            .field("size", &self.image.dimensions())
+            .field("fit", &self.fit)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// A 2x2 texture with a distinct color in each pixel.
+    fn checker() -> RgbaImage {
+        RgbaImage::from_fn(2, 2, |x, y| {
+            Rgba([(x * 100) as u8, (y * 100) as u8, 0, 255])
+        })
+    }
+
+    #[test]
+    fn load_texture_from_file() {
+        let texture = Texture::load(&PathBuf::from("images/test/test_03.png"), TextureFit::Tile);
+        assert!(texture.is_ok());
+    }
+
+    #[test]
+    fn unreadable_texture_is_err() {
+        let path = PathBuf::from("Cargo.toml");
+        let texture = Texture::load(&path, TextureFit::Tile);
+        assert_eq!(texture.unwrap_err(), OliframeError::ImageUnreadable(path));
+    }
+
+    #[test]
+    fn parse_texture_fit() {
+        assert_eq!("tile".parse::<TextureFit>().unwrap(), TextureFit::Tile);
+        assert_eq!(
+            "Stretch".parse::<TextureFit>().unwrap(),
+            TextureFit::Stretch
+        );
+        assert_eq!("cover".parse::<TextureFit>().unwrap(), TextureFit::Cover);
+        assert_eq!("center".parse::<TextureFit>().unwrap(), TextureFit::Center);
+        assert!("mosaic".parse::<TextureFit>().is_err());
+    }
+
+    #[test]
+    fn paint_tiled_texture() {
+        let texture = Texture::new(checker(), TextureFit::Tile);
+        let mut canvas = RgbaImage::new(5, 5);
+        texture.paint(&mut canvas);
+        assert_eq!(canvas.get_pixel(0, 0), canvas.get_pixel(2, 4));
+        assert_eq!(canvas.get_pixel(1, 0), canvas.get_pixel(3, 2));
+        assert_eq!(canvas.get_pixel(4, 1), &Rgba([0, 100, 0, 255]));
+    }
+
+    #[test]
+    fn paint_stretched_and_covering_textures() {
+        for fit in [TextureFit::Stretch, TextureFit::Cover] {
+            let texture = Texture::new(checker(), fit);
+            let mut canvas = RgbaImage::new(8, 4);
+            texture.paint(&mut canvas);
+            assert!(canvas.pixels().all(|p| p[3] == 255));
+        }
+    }
+
+    #[test]
+    fn paint_centered_texture() {
+        let texture = Texture::new(checker(), TextureFit::Center);
+        let mut canvas = RgbaImage::from_pixel(4, 4, Rgba([9, 9, 9, 255]));
+        texture.paint(&mut canvas);
+        assert_eq!(canvas.get_pixel(0, 0), &Rgba([9, 9, 9, 255]));
+        assert_eq!(canvas.get_pixel(1, 1), &Rgba([0, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(2, 2), &Rgba([100, 100, 0, 255]));
+        assert_eq!(canvas.get_pixel(3, 3), &Rgba([9, 9, 9, 255]));
+    }
+}
@@ -0,0 +1,273 @@
+//! The watermark module is responsible for placing a logo over the frame or the image.
+use crate::errors::OliframeError;
+use crate::frame::build;
+use crate::frame::color_space::ColorSpace;
+use crate::frame::composite::{self, Blending};
+use crate::frame::outline::Outline;
+use crate::geometry::{HorizontalPosition, Length, RelativePosition, VerticalPosition, percentage};
+use derive_getters::Getters;
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use strum_macros::{AsRefStr, EnumString};
+
+/// A logo or watermark placed over the frame or the image.
+///
+/// The watermark is decoded once when the configuration is built,
+/// and shared by every image processed in the run.
+/// Its size and margin are resolved against the thickness of the margin it is anchored in
+/// when pinned to the frame, or against the shorter side of the image when pinned to the image.
+#[derive(Clone, Getters, PartialEq)]
+pub struct Watermark {
+    /// The decoded watermark image.
+    image: Arc<RgbaImage>,
+    /// The height of the watermark.
+    size: Length,
+    /// The opacity of the watermark, from 0 to 1.
+    opacity: f32,
+    /// The space between the watermark and the edges of its area.
+    margin: Length,
+    /// The corner or edge of its area where the watermark is anchored.
+    position: RelativePosition,
+    /// The area the watermark is pinned to.
+    area: WatermarkArea,
+}
+
+/// The area a watermark is pinned to.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum WatermarkArea {
+    /// The whole output image, including the frame.
+    #[default]
+    Frame,
+    /// The image inside the frame.
+    Image,
+}
+
+impl Watermark {
+    /// Load a watermark from the given image file.
+    pub fn load(
+        path: &PathBuf,
+        size: Length,
+        margin: Length,
+        position: RelativePosition,
+        area: WatermarkArea,
+    ) -> Result<Self, OliframeError> {
+        let (image, _, metadata) = build::load(path)?;
+        let mut image = image.to_rgba8();
+        if let Some(color_space) = metadata.color_space() {
+            color_space.image_to_srgb(&mut image);
+        }
+        Ok(Self::new(image, size, margin, position, area))
+    }
+
+    /// Create a new, fully opaque watermark from an already decoded image.
+    pub fn new(
+        image: RgbaImage,
+        size: Length,
+        margin: Length,
+        position: RelativePosition,
+        area: WatermarkArea,
+    ) -> Self {
+        Self {
+            image: Arc::new(image),
+            size,
+            opacity: 1.,
+            margin,
+            position,
+            area,
+        }
+    }
+
+    /// Set the opacity of the watermark, as a percentage.
+    pub fn with_opacity(mut self, opacity: f32) -> Result<Self, OliframeError> {
+        self.opacity = percentage(opacity, "Watermark opacity")?;
+        Ok(self)
+    }
+
+    /// Paint the watermark onto the canvas, anchored in the frame or over the image window.
+    ///
+    /// The watermark is converted into the canvas's color space, if it is not sRGB.
+    pub fn paint(
+        &self,
+        canvas: &mut RgbaImage,
+        window: &Outline,
+        color_space: Option<&ColorSpace>,
+        blending: Blending,
+    ) {
+        let (width, height) = canvas.dimensions();
+        let (left, top, right, bottom) = window.bounds(width, height);
+        let (area_x, area_y, area_width, area_height, basis) = match self.area {
+            WatermarkArea::Frame => {
+                // The thickness of the margin the watermark is anchored in.
+                let basis = match (self.position.vertical(), self.position.horizontal()) {
+                    (VerticalPosition::Top, _) => top,
+                    (VerticalPosition::Bottom, _) => height - bottom,
+                    (_, HorizontalPosition::Left) => left,
+                    (_, HorizontalPosition::Right) => width - right,
+                    _ => (bottom - top).min(right - left),
+                };
+                (0, 0, width, height, basis)
+            }
+            WatermarkArea::Image => (
+                left,
+                top,
+                right - left,
+                bottom - top,
+                (right - left).min(bottom - top),
+            ),
+        };
+
+        let mark_height = self.size.to_pixels(basis as f32);
+        let mark_width = (self.image.width() as f32 * mark_height as f32
+            / self.image.height().max(1) as f32)
+            .round() as u32;
+        if mark_width == 0 || mark_height == 0 || self.opacity == 0. {
+            return;
+        }
+        let mut mark = imageops::resize(
+            self.image.as_ref(),
+            mark_width,
+            mark_height,
+            FilterType::Lanczos3,
+        );
+        if let Some(color_space) = color_space {
+            color_space.image_from_srgb(&mut mark);
+        }
+
+        let margin = self.margin.to_pixels(basis as f32) as i64;
+        let (area_x, area_y) = (area_x as i64, area_y as i64);
+        let (area_width, area_height) = (area_width as i64, area_height as i64);
+        let (mark_width, mark_height) = (mark_width as i64, mark_height as i64);
+        let x = match self.position.horizontal() {
+            HorizontalPosition::Left => area_x + margin,
+            HorizontalPosition::Center => area_x + (area_width - mark_width) / 2,
+            HorizontalPosition::Right => area_x + area_width - mark_width - margin,
+        };
+        let y = match self.position.vertical() {
+            VerticalPosition::Top => area_y + margin,
+            VerticalPosition::Center => area_y + (area_height - mark_height) / 2,
+            VerticalPosition::Bottom => area_y + area_height - mark_height - margin,
+        };
+
+        for (dx, dy, pixel) in mark.enumerate_pixels() {
+            let (px, py) = (x + dx as i64, y + dy as i64);
+            if px >= 0 && py >= 0 && px < width as i64 && py < height as i64 {
+                composite::over(
+                    canvas.get_pixel_mut(px as u32, py as u32),
+                    pixel,
+                    self.opacity,
+                    blending,
+                );
+            }
+        }
+    }
+}
+
+impl Debug for Watermark {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watermark")
+            .field("size", &self.size)
+            .field("opacity", &self.opacity)
+            .field("margin", &self.margin)
+            .field("position", &self.position)
+            .field("area", &self.area)
+            .field("dimensions", &self.image.dimensions())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Point, Radii, Size};
+    use crate::test_utils::{bounds, paint_around_window, window};
+    use image::Rgba;
+    use std::str::FromStr;
+
+    /// A red watermark, twice as wide as it is tall.
+    fn watermark(size: &str, margin: &str, position: &str, area: WatermarkArea) -> Watermark {
+        Watermark::new(
+            RgbaImage::from_pixel(20, 10, Rgba([255, 0, 0, 255])),
+            Length::from_str(size).unwrap(),
+            Length::from_str(margin).unwrap(),
+            RelativePosition::from_str(position).unwrap(),
+            area,
+        )
+    }
+
+    /// Paint the watermark around the test window,
+    /// returning the bounds (left, top, right, bottom) of the painted pixels.
+    fn paint(watermark: &Watermark) -> (u32, u32, u32, u32) {
+        bounds(&paint_around_window(|canvas, window| {
+            watermark.paint(canvas, window, None, Blending::Srgb)
+        }))
+    }
+
+    #[test]
+    fn watermark_in_the_frame() {
+        // Half the bottom margin's 40px, centered in it.
+        let bounds = paint(&watermark(
+            "50%",
+            "25%",
+            "bottom right",
+            WatermarkArea::Frame,
+        ));
+        assert_eq!(bounds, (90, 90, 129, 109));
+
+        let bounds = paint(&watermark("10px", "2px", "top left", WatermarkArea::Frame));
+        assert_eq!(bounds, (2, 2, 21, 11));
+    }
+
+    #[test]
+    fn watermark_over_the_image() {
+        // A fifth of the image's 60px height, 5px from its corner.
+        let bounds = paint(&watermark(
+            "20%",
+            "5px",
+            "bottom left",
+            WatermarkArea::Image,
+        ));
+        assert_eq!(bounds, (25, 63, 48, 74));
+        let bounds = paint(&watermark("10px", "0", "center", WatermarkArea::Image));
+        assert_eq!(bounds, (60, 45, 79, 54));
+    }
+
+    #[test]
+    fn watermark_opacity() {
+        let mut canvas = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        let window = window(Point::new(0, 0), Size::new(10, 10), Radii::default());
+        let watermark = watermark("10px", "0", "center", WatermarkArea::Image)
+            .with_opacity(50.)
+            .unwrap();
+        watermark.paint(&mut canvas, &window, None, Blending::Srgb);
+        assert_eq!(canvas.get_pixel(5, 5), &Rgba([255, 128, 128, 255]));
+
+        let watermark = watermark.with_opacity(1.).unwrap();
+        assert_eq!(watermark.opacity(), 0.01);
+        let watermark = watermark.with_opacity(150.);
+        assert!(watermark.is_err());
+    }
+
+    #[test]
+    fn load_watermark() {
+        let watermark = Watermark::load(
+            &PathBuf::from("images/test/test_03.png"),
+            Length::Relative(0.5),
+            Length::default(),
+            RelativePosition::default(),
+            WatermarkArea::Frame,
+        );
+        assert!(watermark.is_ok());
+        let watermark = Watermark::load(
+            &PathBuf::from("Cargo.toml"),
+            Length::Relative(0.5),
+            Length::default(),
+            RelativePosition::default(),
+            WatermarkArea::Frame,
+        );
+        assert!(watermark.is_err());
+    }
+}
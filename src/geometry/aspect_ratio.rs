@@ -1,7 +1,20 @@
 //! Aspect ratio representation and parsing.
 use crate::errors::OliframeError;
+use crate::geometry::Size;
 use derive_getters::Getters;
 use std::str::FromStr;
+use strum_macros::{AsRefStr, EnumString};
+
+/// Named aspect ratios, given as width and height.
+const PRESETS: [(&str, f32, f32); 7] = [
+    ("instagram-portrait", 4., 5.),
+    ("story", 9., 16.),
+    ("a4", 210., 297.),
+    ("letter", 8.5, 11.),
+    ("golden", 1.618_034, 1.),
+    ("square", 1., 1.),
+    ("35mm", 3., 2.),
+];
 
 /// Final image aspect ratio.
 ///
@@ -10,52 +23,104 @@ use std::str::FromStr;
 /// - Decimal: `1.5`
 /// - Fraction with colon: `16:9`
 /// - Fraction with slash: `16/9`
+/// - Named preset: `instagram-portrait` (4:5), `story` (9:16), `a4`, `letter`,
+///   `golden`, `square` or `35mm` (3:2)
 ///
+/// Any of these can end with a `~` (e.g.: `4:5~`) to turn the ratio to the orientation of each image,
+/// so portrait ratios become landscape ones for landscape images and vice versa.
 #[derive(Debug, Getters, PartialEq)]
 pub struct AspectRatio {
     /// The aspect ratio value.
     inner: f32,
+    /// Whether the ratio is turned to match the orientation of each image.
+    orientation_aware: bool,
+}
+
+/// How the output image is brought to its aspect ratio.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum AspectRatioMode {
+    /// The frame is padded out on two sides.
+    #[default]
+    Pad,
+    /// The image is cropped, so the frame keeps the thickness of its margins.
+    Crop,
+}
+
+impl AspectRatio {
+    /// The aspect ratio for an image of the given size,
+    /// turned to the image's orientation if the ratio is orientation-aware.
+    ///
+    /// Square images keep the ratio as given.
+    pub fn for_size(&self, size: &Size) -> f32 {
+        let landscape_image = size.width() > size.height();
+        let portrait_image = size.width() < size.height();
+        let turned = (landscape_image && self.inner < 1.) || (portrait_image && self.inner > 1.);
+        if self.orientation_aware && turned {
+            1. / self.inner
+        } else {
+            self.inner
+        }
+    }
 }
 
 impl FromStr for AspectRatio {
     type Err = OliframeError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        if let Ok(inner) = input.parse::<f32>() {
-            return Ok(Self { inner });
-        }
+        let (ratio, orientation_aware) = match input.trim().strip_suffix('~') {
+            Some(ratio) => (ratio.trim(), true),
+            None => (input.trim(), false),
+        };
+        let preset = PRESETS
+            .iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(ratio));
+        let inner = match preset {
+            Some((_, width, height)) => width / height,
+            None => parse_ratio(ratio)?,
+        };
 
-        let delimiters = [':', '/'];
-        for delimiter in &delimiters {
-            if let Some((left, right)) = input.split_once(*delimiter) {
-                let numerator = left.trim().parse::<f32>();
-                let denominator = right.trim().parse::<f32>();
-
-                match (numerator, denominator) {
-                    (Ok(numerator), Ok(denominator)) if denominator != 0.0 => {
-                        if numerator < 0.0 || denominator < 0.0 {
-                            return Err(OliframeError::InvalidInput(
-                                "Aspect ratio values cannot be negative".to_string(),
-                            ));
-                        }
-                        return Ok(Self {
-                            inner: numerator / denominator,
-                        });
-                    }
-                    (Ok(_), Ok(0.0)) => {
-                        return Err(OliframeError::InvalidInput(format!(
-                            "Denominator cannot be zero: {input}"
-                        )));
+        Ok(Self {
+            inner,
+            orientation_aware,
+        })
+    }
+}
+
+/// Parse a ratio given as a decimal or a fraction.
+fn parse_ratio(input: &str) -> Result<f32, OliframeError> {
+    if let Ok(inner) = input.parse::<f32>() {
+        return Ok(inner);
+    }
+
+    let delimiters = [':', '/'];
+    for delimiter in &delimiters {
+        if let Some((left, right)) = input.split_once(*delimiter) {
+            let numerator = left.trim().parse::<f32>();
+            let denominator = right.trim().parse::<f32>();
+
+            match (numerator, denominator) {
+                (Ok(numerator), Ok(denominator)) if denominator != 0.0 => {
+                    if numerator < 0.0 || denominator < 0.0 {
+                        return Err(OliframeError::InvalidInput(
+                            "Aspect ratio values cannot be negative".to_string(),
+                        ));
                     }
-                    _ => continue,
+                    return Ok(numerator / denominator);
+                }
+                (Ok(_), Ok(0.0)) => {
+                    return Err(OliframeError::InvalidInput(format!(
+                        "Denominator cannot be zero: {input}"
+                    )));
                 }
+                _ => continue,
             }
         }
-
-        Err(OliframeError::InvalidInput(format!(
-            "Invalid ratio: {input}"
-        )))
     }
+
+    Err(OliframeError::InvalidInput(format!(
+        "Invalid ratio: {input}"
+    )))
 }
 
 #[cfg(test)]
@@ -80,6 +145,35 @@ mod tests {
         assert_eq!(ratio.inner, 16.0 / 9.0);
     }
 
+    #[test]
+    fn parse_presets() {
+        let ratio = "instagram-portrait".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.inner, 4.0 / 5.0);
+        let ratio = "Story".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.inner, 9.0 / 16.0);
+        let ratio = "a4".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.inner, 210.0 / 297.0);
+        let ratio = "35mm".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.inner, 1.5);
+        assert!(!ratio.orientation_aware);
+        assert!("instagram".parse::<AspectRatio>().is_err());
+    }
+
+    #[test]
+    fn orientation_aware_ratio() {
+        let ratio = "4:5~".parse::<AspectRatio>().unwrap();
+        assert!(ratio.orientation_aware);
+        assert_eq!(ratio.for_size(&Size::new(300, 200)), 5.0 / 4.0);
+        assert_eq!(ratio.for_size(&Size::new(200, 300)), 4.0 / 5.0);
+        assert_eq!(ratio.for_size(&Size::new(200, 200)), 4.0 / 5.0);
+
+        let ratio = "story~".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.for_size(&Size::new(300, 200)), 16.0 / 9.0);
+
+        let ratio = "4:5".parse::<AspectRatio>().unwrap();
+        assert_eq!(ratio.for_size(&Size::new(300, 200)), 4.0 / 5.0);
+    }
+
     #[test]
     fn divide_by_zero_is_err() {
         let ratio = "16:0".parse::<AspectRatio>();
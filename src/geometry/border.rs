@@ -1,10 +1,12 @@
 //! A border is the specific pixel widths of the top, right, bottom,
 //! and left sides surrounding the image.
+use crate::geometry::Side;
 use derive_getters::Getters;
+use std::ops::Add;
 
 /// A border is the specific pixel widths of the top, right, bottom,
 /// and left sides of surrounding the image.
-#[derive(Debug, Getters)]
+#[derive(Clone, Debug, Default, Getters, PartialEq)]
 pub struct Border {
     /// The width of the top border.
     top: u32,
@@ -26,4 +28,37 @@ impl Border {
             left,
         }
     }
+
+    /// Create a border with the given width on one side only.
+    pub fn on_side(side: Side, width: u32) -> Self {
+        match side {
+            Side::Top => Border::new(width, 0, 0, 0),
+            Side::Right => Border::new(0, width, 0, 0),
+            Side::Bottom => Border::new(0, 0, width, 0),
+            Side::Left => Border::new(0, 0, 0, width),
+        }
+    }
+
+    /// Grow each side of the border to at least the width of the matching side of another border.
+    pub fn at_least(&self, other: &Border) -> Self {
+        Border {
+            top: self.top.max(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+            left: self.left.max(other.left),
+        }
+    }
+}
+
+impl Add for Border {
+    type Output = Border;
+
+    fn add(self, other: Border) -> Border {
+        Border {
+            top: self.top.saturating_add(other.top),
+            right: self.right.saturating_add(other.right),
+            bottom: self.bottom.saturating_add(other.bottom),
+            left: self.left.saturating_add(other.left),
+        }
+    }
 }
@@ -0,0 +1,118 @@
+//! Corner radius module.
+use crate::errors::OliframeError;
+use crate::geometry::{Length, Radii, Size};
+use derive_getters::Getters;
+use std::str::FromStr;
+
+/// The corner radius defines how much the corners of the image are rounded.
+///
+/// The user can specify each radius in pixels (`20`, `20px`)
+/// or as a percentage of the shorter side of the image (`5%`).
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct CornerRadius {
+    /// The values of the radii.
+    ///
+    /// There should be between one and four values, following CSS `border-radius`.
+    /// - one value: all corners have the same radius.
+    /// - two values: top-left/bottom-right and top-right/bottom-left.
+    /// - three values: top-left, top-right/bottom-left, and bottom-right.
+    /// - four values: top-left, top-right, bottom-right, and bottom-left.
+    values: Vec<Length>,
+}
+
+impl CornerRadius {
+    /// Given the size of a specific image, return the pixel-specific radius of each corner.
+    ///
+    /// Radii are limited to half of the shorter side, so opposite corners never overlap.
+    pub fn to_radii_with_size(&self, size: &Size) -> Radii {
+        let short_side = size.width().min(size.height());
+        let radius = |i: usize| {
+            self.values[i]
+                .to_pixels(short_side as f32)
+                .min(short_side / 2)
+        };
+        match self.values.len() {
+            1 => Radii::uniform(radius(0)),
+            2 => Radii::new(radius(0), radius(1), radius(0), radius(1)),
+            3 => Radii::new(radius(0), radius(1), radius(2), radius(1)),
+            4 => Radii::new(radius(0), radius(1), radius(2), radius(3)),
+            _ => unreachable!("Invalid number of corner radius values."),
+        }
+    }
+}
+
+impl FromStr for CornerRadius {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let values = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(Length::from_str)
+            .collect::<Result<Vec<Length>, OliframeError>>()?;
+
+        let len = values.len();
+        if !(1..=4).contains(&len) {
+            return Err(OliframeError::InvalidInput(format!(
+                "Corner radius must be specified with 1-4 values (received {len} values)."
+            )));
+        }
+
+        Ok(CornerRadius { values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corner_radius_from_str() {
+        let radius = CornerRadius::from_str("10").unwrap();
+        assert_eq!(radius.values(), &[Length::Pixels(10.)]);
+
+        let radius = CornerRadius::from_str("10px, 5%").unwrap();
+        assert_eq!(
+            radius.values(),
+            &[Length::Pixels(10.), Length::Relative(0.05)]
+        );
+    }
+
+    #[test]
+    fn invalid_corner_radius_is_err() {
+        assert!(CornerRadius::from_str("").is_err());
+        assert!(CornerRadius::from_str("1 2 3 4 5").is_err());
+        assert!(CornerRadius::from_str("-10").is_err());
+        assert!(CornerRadius::from_str("ten").is_err());
+    }
+
+    #[test]
+    fn corner_radius_to_radii() {
+        let size = Size::new(400, 200);
+
+        let radii = CornerRadius::from_str("10")
+            .unwrap()
+            .to_radii_with_size(&size);
+        assert_eq!(radii, Radii::uniform(10));
+
+        let radii = CornerRadius::from_str("10 5%")
+            .unwrap()
+            .to_radii_with_size(&size);
+        assert_eq!(radii, Radii::new(10, 10, 10, 10));
+
+        let radii = CornerRadius::from_str("1 2 3")
+            .unwrap()
+            .to_radii_with_size(&size);
+        assert_eq!(radii, Radii::new(1, 2, 3, 2));
+
+        let radii = CornerRadius::from_str("1 2 3 4")
+            .unwrap()
+            .to_radii_with_size(&size);
+        assert_eq!(radii, Radii::new(1, 2, 3, 4));
+
+        let radii = CornerRadius::from_str("75%")
+            .unwrap()
+            .to_radii_with_size(&size);
+        assert_eq!(radii, Radii::uniform(100));
+    }
+}
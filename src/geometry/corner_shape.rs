@@ -0,0 +1,134 @@
+//! The corner shape is the curve used to cut the corners of the image.
+use crate::errors::OliframeError;
+use std::str::FromStr;
+
+/// The exponent of a squircle when the user does not give one.
+const DEFAULT_SQUIRCLE_EXPONENT: f32 = 5.;
+
+/// The curve used to cut the corners of the image.
+///
+/// Every shape is a superellipse `|u / r|ⁿ + |v / r|ⁿ ≤ 1` with a different exponent `n`.
+/// The user can provide the shape in the following formats:
+/// - `circle`: a circular arc (n = 2)
+/// - `squircle` or `squircle:4`: a superellipse, with an exponent of 5 unless given
+/// - `chamfer`: a straight cut (n = 1)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CornerShape {
+    /// A circular arc.
+    #[default]
+    Circle,
+    /// A superellipse with the given exponent, flatter than a circle.
+    Squircle(f32),
+    /// A straight cut across the corner.
+    Chamfer,
+}
+
+impl CornerShape {
+    /// The exponent of the superellipse describing the shape.
+    pub fn exponent(&self) -> f32 {
+        match self {
+            CornerShape::Circle => 2.,
+            CornerShape::Squircle(exponent) => *exponent,
+            CornerShape::Chamfer => 1.,
+        }
+    }
+
+    /// Whether the point `(u, v)`, measured from the corner's center, lies inside the shape.
+    pub fn contains(&self, u: f32, v: f32, radius: f32) -> bool {
+        let n = self.exponent();
+        (u / radius).powf(n) + (v / radius).powf(n) <= 1.
+    }
+
+    /// The height of the shape at the distance `u` from the corner's center.
+    pub fn height(&self, u: f32, radius: f32) -> f32 {
+        if u >= radius {
+            return 0.;
+        }
+        let n = self.exponent();
+        radius * (1. - (u / radius).powf(n)).powf(1. / n)
+    }
+}
+
+impl FromStr for CornerShape {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim().to_lowercase();
+        let (name, exponent) = match input.split_once(':') {
+            Some((name, exponent)) => (name, Some(exponent)),
+            None => (input.as_str(), None),
+        };
+
+        match (name, exponent) {
+            ("circle", None) => Ok(CornerShape::Circle),
+            ("chamfer", None) => Ok(CornerShape::Chamfer),
+            ("squircle", None) => Ok(CornerShape::Squircle(DEFAULT_SQUIRCLE_EXPONENT)),
+            ("squircle", Some(exponent)) => exponent
+                .parse::<f32>()
+                .ok()
+                .filter(|n| *n >= 1.)
+                .map(CornerShape::Squircle)
+                .ok_or_else(|| {
+                    OliframeError::InvalidInput(format!(
+                        "Squircle exponents must be numbers of at least 1 (received {exponent})."
+                    ))
+                }),
+            _ => Err(OliframeError::InvalidInput(format!(
+                "Invalid corner shape: {input}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_corner_shapes() {
+        assert_eq!(
+            "circle".parse::<CornerShape>().unwrap(),
+            CornerShape::Circle
+        );
+        assert_eq!(
+            "Chamfer".parse::<CornerShape>().unwrap(),
+            CornerShape::Chamfer
+        );
+        assert_eq!(
+            "squircle".parse::<CornerShape>().unwrap(),
+            CornerShape::Squircle(5.)
+        );
+        assert_eq!(
+            "squircle:4".parse::<CornerShape>().unwrap(),
+            CornerShape::Squircle(4.)
+        );
+    }
+
+    #[test]
+    fn invalid_corner_shapes_are_err() {
+        assert!("oval".parse::<CornerShape>().is_err());
+        assert!("squircle:0.5".parse::<CornerShape>().is_err());
+        assert!("squircle:big".parse::<CornerShape>().is_err());
+        assert!("circle:2".parse::<CornerShape>().is_err());
+    }
+
+    #[test]
+    fn shapes_cut_the_corner_differently() {
+        // A point just inside the diagonal of a circle of radius 10.
+        let (u, v) = (7., 7.);
+        assert!(CornerShape::Circle.contains(u, v, 10.));
+        assert!(CornerShape::Squircle(5.).contains(u, v, 10.));
+        assert!(!CornerShape::Chamfer.contains(u, v, 10.));
+        assert!(!CornerShape::Circle.contains(8., 8., 10.));
+        assert!(CornerShape::Squircle(5.).contains(8., 8., 10.));
+    }
+
+    #[test]
+    fn shape_heights() {
+        assert!((CornerShape::Chamfer.height(4., 10.) - 6.).abs() < 1e-5);
+        assert!((CornerShape::Circle.height(6., 10.) - 8.).abs() < 1e-5);
+        assert_eq!(CornerShape::Circle.height(10., 10.), 0.);
+        assert!(CornerShape::Squircle(5.).height(6., 10.) > 8.);
+    }
+}
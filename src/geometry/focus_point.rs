@@ -0,0 +1,75 @@
+//! A focus point marks the part of an image to keep in view when it is cropped.
+use crate::errors::OliframeError;
+use derive_getters::Getters;
+use std::str::FromStr;
+
+/// The part of an image to keep in view when it is cropped,
+/// as fractions of the image's width and height from its top-left corner.
+///
+/// The user can provide the focus point as two percentages, separated by a comma (e.g.: `30,60` or `30%,60%`).
+#[derive(Clone, Debug, Getters, PartialEq)]
+pub struct FocusPoint {
+    /// The horizontal position of the point, from 0 (left) to 1 (right).
+    x: f32,
+    /// The vertical position of the point, from 0 (top) to 1 (bottom).
+    y: f32,
+}
+
+impl FocusPoint {
+    /// The offset of a span of the given length within a dimension of the given length,
+    /// centered as close to the point's fraction of the dimension as it can be without overflowing.
+    pub fn offset(fraction: f32, span: u32, length: u32) -> u32 {
+        let offset = (fraction * length as f32 - span as f32 / 2.).round();
+        offset.clamp(0., length.saturating_sub(span) as f32) as u32
+    }
+}
+
+impl FromStr for FocusPoint {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || OliframeError::InvalidInput(format!("Invalid focus point: {input}"));
+        let (x, y) = input.split_once(',').ok_or_else(invalid)?;
+        let percentage = |value: &str| {
+            let value = value.trim();
+            value
+                .strip_suffix('%')
+                .unwrap_or(value)
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| (0. ..=100.).contains(value))
+                .map(|value| value / 100.)
+                .ok_or_else(invalid)
+        };
+
+        Ok(Self {
+            x: percentage(x)?,
+            y: percentage(y)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_focus_points() {
+        let focus = "30,60".parse::<FocusPoint>().unwrap();
+        assert_eq!((focus.x(), focus.y()), (0.3, 0.6));
+        let focus = "50%, 0%".parse::<FocusPoint>().unwrap();
+        assert_eq!((focus.x(), focus.y()), (0.5, 0.));
+        assert!("50".parse::<FocusPoint>().is_err());
+        assert!("50,120".parse::<FocusPoint>().is_err());
+        assert!("left,top".parse::<FocusPoint>().is_err());
+    }
+
+    #[test]
+    fn offset_around_focus_point() {
+        assert_eq!(FocusPoint::offset(0.5, 40, 100), 30);
+        assert_eq!(FocusPoint::offset(0.1, 40, 100), 0);
+        assert_eq!(FocusPoint::offset(0.9, 40, 100), 60);
+        assert_eq!(FocusPoint::offset(0.5, 100, 100), 0);
+    }
+}
@@ -0,0 +1,127 @@
+//! A length is a distance given either in pixels or relative to the size of the image.
+use crate::errors::OliframeError;
+use crate::geometry::Size;
+use std::str::FromStr;
+
+/// The largest length in pixels.
+const MAX_PIXELS: f32 = Size::MAX_SIDE as f32;
+
+/// The largest relative length, as a percentage.
+const MAX_PERCENTAGE: f32 = 1_000.;
+
+/// A distance given either in pixels or relative to the size of the image.
+///
+/// The user can provide the length in the following formats:
+/// - Pixels: `4` or `4px`
+/// - Percentage: `0.5%`
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Length {
+    /// An absolute number of pixels.
+    Pixels(f32),
+    /// A fraction of a reference dimension of the image.
+    Relative(f32),
+}
+
+impl Length {
+    /// Resolve the length to pixels, given the reference dimension for relative lengths.
+    pub fn to_pixels(&self, basis: f32) -> u32 {
+        match self {
+            Length::Pixels(px) => px.round() as u32,
+            Length::Relative(fraction) => (fraction * basis).round() as u32,
+        }
+    }
+}
+
+/// Convert a user-supplied percentage, from 0 to 100, into a fraction.
+pub fn percentage(value: f32, name: &str) -> Result<f32, OliframeError> {
+    if !(0. ..=100.).contains(&value) {
+        return Err(OliframeError::InvalidInput(format!(
+            "{name} must be between 0 and 100%."
+        )));
+    }
+    Ok(value / 100.)
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Pixels(0.)
+    }
+}
+
+impl FromStr for Length {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (value, max, length): (&str, f32, fn(f32) -> Length) =
+            if let Some(v) = input.strip_suffix('%') {
+                (v, MAX_PERCENTAGE, |v| Length::Relative(v / 100.))
+            } else {
+                (
+                    input.strip_suffix("px").unwrap_or(input),
+                    MAX_PIXELS,
+                    Length::Pixels,
+                )
+            };
+
+        let value = value
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| OliframeError::InvalidInput(format!("Invalid length: {input}")))?;
+        if value < 0. {
+            return Err(OliframeError::InvalidInput(format!(
+                "Lengths cannot be negative: {input}"
+            )));
+        }
+        if value > max {
+            return Err(OliframeError::InvalidInput(format!(
+                "Lengths cannot be larger than {MAX_PIXELS}px or {MAX_PERCENTAGE}%: {input}"
+            )));
+        }
+
+        Ok(length(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lengths() {
+        assert_eq!("4".parse::<Length>().unwrap(), Length::Pixels(4.));
+        assert_eq!("4px".parse::<Length>().unwrap(), Length::Pixels(4.));
+        assert_eq!("50%".parse::<Length>().unwrap(), Length::Relative(0.5));
+    }
+
+    #[test]
+    fn invalid_lengths_are_err() {
+        assert!("four".parse::<Length>().is_err());
+        assert!("-4px".parse::<Length>().is_err());
+        assert!("4em".parse::<Length>().is_err());
+        assert!("inf".parse::<Length>().is_err());
+        assert!("NaN%".parse::<Length>().is_err());
+        assert!("1e10".parse::<Length>().is_err());
+        assert!("2000%".parse::<Length>().is_err());
+    }
+
+    #[test]
+    fn percentages_to_fractions() {
+        assert_eq!(percentage(50., "Opacity"), Ok(0.5));
+        assert_eq!(percentage(1., "Opacity"), Ok(0.01));
+        assert_eq!(
+            percentage(150., "Opacity").unwrap_err().to_string(),
+            "Invalid input: Opacity must be between 0 and 100%."
+        );
+        assert!(percentage(-1., "Opacity").is_err());
+    }
+
+    #[test]
+    fn resolve_lengths() {
+        assert_eq!(Length::Pixels(4.4).to_pixels(1000.), 4);
+        assert_eq!(Length::Relative(0.005).to_pixels(1000.), 5);
+    }
+}
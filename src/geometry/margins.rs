@@ -1,15 +1,23 @@
 //! Margins module.
 use crate::errors::OliframeError;
-use crate::geometry::Size;
 use crate::geometry::border::Border;
+use crate::geometry::{Length, Size};
 use derive_getters::Getters;
 use std::str::FromStr;
+use strum_macros::{AsRefStr, EnumString};
 
-/// Margins define the relative space around an image, proportionate to the size of the image.
+/// The number of millimeters in an inch.
+const MM_PER_INCH: f32 = 25.4;
+
+/// Margins define the space around an image, either in pixels or proportionate to the size of the image.
 ///
-/// The user can specify the width of the margins on each side
-/// of the image in percentage (%).
-#[derive(Clone, Debug, Getters)]
+/// The user can specify the width of the margins on each side of the image
+/// in pixels (`40px`), as a percentage (`5%`) or in physical units (`5mm`, `0.25in`)
+/// converted to pixels at the resolution given after the values (`5mm@300dpi`).
+/// Values without a unit are percentages, or fractions when they are not greater than 1,
+/// for compatibility with earlier versions; they are read with a warning, since other lengths
+/// without a unit are pixels.
+#[derive(Clone, Debug, Getters, PartialEq)]
 pub struct Margins {
     /// The values of the margins.
     ///
@@ -18,45 +26,104 @@ pub struct Margins {
     /// - two values: top/bottom and left/right margins are the same.
     /// - three values: top, left/right, and bottom margins are different.
     /// - four values: top, right, bottom, and left margins are different.
-    values: Vec<f32>,
+    values: Vec<Length>,
+}
+
+/// The dimension that relative margins are a fraction of.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum MarginBasis {
+    /// The shorter side of the image.
+    Short,
+    /// The longer side of the image.
+    Long,
+    /// The width of the image.
+    Width,
+    /// The height of the image.
+    Height,
+    /// The average of the image's width and height.
+    #[default]
+    Average,
+    /// The diagonal of the image.
+    Diagonal,
+    /// The average of the width and height of the output image,
+    /// made of the image and all of the mats and margins around it.
+    Output,
+}
+
+impl MarginBasis {
+    /// The dimension of an image of the given size that relative margins are a fraction of.
+    ///
+    /// The output size depends on the margins themselves, so for the output basis
+    /// this is only the average of the image's sides; see [`MarginBasis::output_dimension`].
+    pub fn dimension(&self, size: &Size) -> f32 {
+        let (width, height) = (size.width() as f32, size.height() as f32);
+        match self {
+            MarginBasis::Short => width.min(height),
+            MarginBasis::Long => width.max(height),
+            MarginBasis::Width => width,
+            MarginBasis::Height => height,
+            MarginBasis::Average | MarginBasis::Output => (width + height) / 2.,
+            MarginBasis::Diagonal => width.hypot(height),
+        }
+    }
+
+    /// The average of the output's sides around an image of the given size,
+    /// framed by the given stack of margins all measured against that output.
+    pub fn output_dimension<'a>(size: &Size, stack: impl IntoIterator<Item = &'a Margins>) -> f32 {
+        // Each margin adds half its thickness to the average of the output's sides,
+        // so the relative margins are solved for the output they are a fraction of.
+        let (pixels, fraction) = stack.into_iter().flat_map(Margins::sides).fold(
+            (0., 0.),
+            |(pixels, fraction), side| match side {
+                Length::Pixels(px) => (pixels + px, fraction),
+                Length::Relative(f) => (pixels, fraction + f),
+            },
+        );
+        // Relative margins of 200% or more have no solution, so they are capped
+        // at a hundred times the size they would have alone rather than growing without bound.
+        (MarginBasis::Output.dimension(size) + pixels / 2.) / (1. - fraction / 2.).max(0.01)
+    }
 }
 
 impl Margins {
-    /// Given the size of a specific image, return the pixel-specific border that represents the margins.
-    pub fn to_border_with_size(&self, size: &Size) -> Border {
-        let dim = (size.width() + size.height()) as f32 / 2.;
-        match self.values.len() {
-            1 => {
-                let side = (self.values[0] * dim).round() as u32;
-                Border::new(side, side, side, side)
-            }
-            2 => {
-                let vertical = (self.values[0] * dim).round() as u32;
-                let horizontal = (self.values[1] * dim).round() as u32;
-                Border::new(vertical, horizontal, vertical, horizontal)
-            }
-            3 => {
-                let top = (self.values[0] * dim).round() as u32;
-                let side = (self.values[1] * dim).round() as u32;
-                let bottom = (self.values[2] * dim).round() as u32;
-                Border::new(top, side, bottom, side)
-            }
-            4 => {
-                let top = (self.values[0] * dim).round() as u32;
-                let right = (self.values[1] * dim).round() as u32;
-                let bottom = (self.values[2] * dim).round() as u32;
-                let left = (self.values[3] * dim).round() as u32;
-                Border::new(top, right, bottom, left)
-            }
+    /// Return the pixel-specific border that represents the margins,
+    /// with relative margins measured against the given dimension.
+    pub fn to_border(&self, dim: f32) -> Border {
+        let [top, right, bottom, left] = self.sides().map(|side| side.to_pixels(dim));
+        Border::new(top, right, bottom, left)
+    }
+
+    /// The sum of the relative margins of all four sides, as a fraction of the basis.
+    ///
+    /// Margins measured against the output only fit in it if the relative margins
+    /// around the image add up to less than 2, since they take up as much of the output's
+    /// average side as half their sum.
+    pub fn relative_total(&self) -> f32 {
+        self.sides()
+            .iter()
+            .map(|side| match side {
+                Length::Pixels(_) => 0.,
+                Length::Relative(f) => *f,
+            })
+            .sum()
+    }
+
+    /// The margins of the top, right, bottom and left sides, expanded as in CSS.
+    fn sides(&self) -> [&Length; 4] {
+        let v = &self.values;
+        match v.len() {
+            1 => [&v[0], &v[0], &v[0], &v[0]],
+            2 => [&v[0], &v[1], &v[0], &v[1]],
+            3 => [&v[0], &v[1], &v[2], &v[1]],
+            4 => [&v[0], &v[1], &v[2], &v[3]],
             _ => unreachable!("Invalid number of margin values."),
         }
     }
 
     /// Create a new Margins instance with the given values,
     /// ensuring that there are between one and four values.
-    ///
-    /// Values are converted to fractions if they are greater than 1.
-    fn try_new(values: Vec<f32>) -> Result<Self, OliframeError> {
+    fn try_new(values: Vec<Length>) -> Result<Self, OliframeError> {
         let len = values.len();
         if !(1..=4).contains(&len) {
             return Err(OliframeError::InvalidInput(format!(
@@ -64,26 +131,13 @@ impl Margins {
             )));
         }
 
-        let values = values
-            .into_iter()
-            .map(|v| if v > 1. { v / 100. } else { v })
-            .map(|v| {
-                if v < 0. {
-                    return Err(OliframeError::InvalidInput(
-                        "Negative margin values are not allowed.".to_string(),
-                    ));
-                }
-                Ok(v)
-            })
-            .collect::<Result<Vec<f32>, OliframeError>>()?;
-
         Ok(Margins { values })
     }
 }
 
 impl Default for Margins {
     fn default() -> Self {
-        Margins::try_new(vec![5.]).expect("Failed to create default Margins.")
+        Margins::try_new(vec![Length::Relative(0.05)]).expect("Failed to create default Margins.")
     }
 }
 
@@ -91,22 +145,73 @@ impl FromStr for Margins {
     type Err = OliframeError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let re = regex::Regex::new(r"(?P<value>-?\d*\.?\d+)").expect("Invalid regex");
-        let values = re
-            .captures_iter(input)
-            .map(|caps| {
-                caps.name("value")
-                    .expect("Expected value not found.")
-                    .as_str()
-                    .parse::<f32>()
-                    .map_err(|_| OliframeError::InvalidInput("Invalid margin value.".to_string()))
-            })
-            .collect::<Result<Vec<f32>, OliframeError>>()?;
+        let (values, dpi) = match input.rsplit_once('@') {
+            Some((values, dpi)) => (values, Some(parse_dpi(dpi)?)),
+            None => (input, None),
+        };
+
+        let values = values
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| parse_margin(v, dpi))
+            .collect::<Result<Vec<Length>, OliframeError>>()?;
 
         Margins::try_new(values)
     }
 }
 
+/// Parse a single margin value, converting physical units to pixels at the given resolution.
+fn parse_margin(input: &str, dpi: Option<f32>) -> Result<Length, OliframeError> {
+    let re =
+        regex::Regex::new(r"^(?P<value>-?\d*\.?\d+)(?P<unit>px|%|mm|in)?$").expect("Invalid regex");
+    let caps = re
+        .captures(input)
+        .ok_or_else(|| OliframeError::InvalidInput(format!("Invalid margin value: {input}")))?;
+    let value = caps["value"]
+        .parse::<f32>()
+        .map_err(|_| OliframeError::InvalidInput(format!("Invalid margin value: {input}")))?;
+    if value < 0. {
+        return Err(OliframeError::InvalidInput(
+            "Negative margin values are not allowed.".to_string(),
+        ));
+    }
+
+    let inches = |inches: f32| {
+        dpi.map(|dpi| Length::Pixels(inches * dpi)).ok_or_else(|| {
+            OliframeError::InvalidInput(format!(
+                "Margins in mm or in need a resolution (e.g.: \"{input}@300dpi\")."
+            ))
+        })
+    };
+    match caps.name("unit").map(|unit| unit.as_str()) {
+        Some("px") => Ok(Length::Pixels(value)),
+        Some("%") => Ok(Length::Relative(value / 100.)),
+        Some("mm") => inches(value / MM_PER_INCH),
+        Some("in") => inches(value),
+        _ => {
+            // Unlike other lengths, margins without a unit are relative, as they always were.
+            let fraction = if value > 1. { value / 100. } else { value };
+            log::warn!(
+                "The margin \"{input}\" has no unit and is read as {}%; add \"%\" or \"px\" to be explicit.",
+                fraction * 100.
+            );
+            Ok(Length::Relative(fraction))
+        }
+    }
+}
+
+/// Parse the resolution used to convert physical units to pixels, such as "300dpi".
+fn parse_dpi(input: &str) -> Result<f32, OliframeError> {
+    let input = input.trim();
+    input
+        .strip_suffix("dpi")
+        .unwrap_or(input)
+        .parse::<f32>()
+        .ok()
+        .filter(|dpi| *dpi > 0.)
+        .ok_or_else(|| OliframeError::InvalidInput(format!("Invalid resolution: {input}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,22 +219,120 @@ mod tests {
     #[test]
     fn default_margins() {
         let margins = Margins::default();
-        assert_eq!(margins.values(), &[0.05]);
+        assert_eq!(margins.values(), &[Length::Relative(0.05)]);
     }
 
     #[test]
     fn margins_from_str() {
         let margins = Margins::from_str("10").unwrap();
-        assert_eq!(margins.values(), &[0.1]);
+        assert_eq!(margins.values(), &[Length::Relative(0.1)]);
 
         let margins = Margins::from_str("10 20").unwrap();
-        assert_eq!(margins.values(), &[0.1, 0.2]);
+        assert_eq!(
+            margins.values(),
+            &[Length::Relative(0.1), Length::Relative(0.2)]
+        );
 
         let margins = Margins::from_str("10 20 30").unwrap();
-        assert_eq!(margins.values(), &[0.1, 0.2, 0.3]);
+        assert_eq!(
+            margins.values(),
+            &[
+                Length::Relative(0.1),
+                Length::Relative(0.2),
+                Length::Relative(0.3)
+            ]
+        );
 
         let margins = Margins::from_str("10 20 30 40").unwrap();
-        assert_eq!(margins.values(), &[0.1, 0.2, 0.3, 0.4]);
+        assert_eq!(
+            margins.values(),
+            &[
+                Length::Relative(0.1),
+                Length::Relative(0.2),
+                Length::Relative(0.3),
+                Length::Relative(0.4)
+            ]
+        );
+    }
+
+    #[test]
+    fn unitless_margins_are_relative() {
+        let margins = Margins::from_str("5 0.5 1 2px").unwrap();
+        assert_eq!(
+            margins.values(),
+            &[
+                Length::Relative(0.05),
+                Length::Relative(0.5),
+                Length::Relative(1.),
+                Length::Pixels(2.)
+            ]
+        );
+    }
+
+    #[test]
+    fn margins_with_units_from_str() {
+        let margins = Margins::from_str("40px,5%").unwrap();
+        assert_eq!(
+            margins.values(),
+            &[Length::Pixels(40.), Length::Relative(0.05)]
+        );
+
+        let margins = Margins::from_str("5mm, 0.5in, 2px 0.5 @300dpi").unwrap();
+        let values = margins.values();
+        assert!((values[0].to_pixels(0.) as f32 - 5. / 25.4 * 300.).abs() < 1.);
+        assert_eq!(values[1], Length::Pixels(150.));
+        assert_eq!(values[2], Length::Pixels(2.));
+        assert_eq!(values[3], Length::Relative(0.5));
+    }
+
+    #[test]
+    fn invalid_margin_units_are_err() {
+        assert!(Margins::from_str("5em").is_err());
+        assert!(Margins::from_str("5mm").is_err());
+        assert!(Margins::from_str("5mm@0dpi").is_err());
+        assert!(Margins::from_str("5mm@dpi").is_err());
+    }
+
+    #[test]
+    fn margins_to_border_with_basis() {
+        let margins = Margins::from_str("10,4px").unwrap();
+        let size = Size::new(300, 400);
+        let border = |basis: MarginBasis| margins.to_border(basis.dimension(&size));
+        assert_eq!(border(MarginBasis::Short), Border::new(30, 4, 30, 4));
+        assert_eq!(border(MarginBasis::Long), Border::new(40, 4, 40, 4));
+        assert_eq!(border(MarginBasis::Width), Border::new(30, 4, 30, 4));
+        assert_eq!(border(MarginBasis::Height), Border::new(40, 4, 40, 4));
+        assert_eq!(border(MarginBasis::Average), Border::new(35, 4, 35, 4));
+        assert_eq!(border(MarginBasis::Diagonal), Border::new(50, 4, 50, 4));
+        // The output is (308 + 478.7) / 2 = 393.3 on average, a tenth of which is 39.3.
+        let output = MarginBasis::output_dimension(&size, [&margins]);
+        assert_eq!(margins.to_border(output), Border::new(39, 4, 39, 4));
+    }
+
+    #[test]
+    fn output_dimension_of_stacked_margins() {
+        let inner = Margins::from_str("10").unwrap();
+        let outer = Margins::from_str("10,4px").unwrap();
+        let size = Size::new(100, 100);
+        // The stack takes up 30% of the output's average side, which is 100 + 4 + 30% of it.
+        let output = MarginBasis::output_dimension(&size, [&inner, &outer]);
+        assert!((output - 104. / 0.7).abs() < 0.01);
+        assert_eq!(inner.to_border(output), Border::new(15, 15, 15, 15));
+        assert_eq!(outer.to_border(output), Border::new(15, 4, 15, 4));
+    }
+
+    #[test]
+    fn relative_total_of_margins() {
+        assert_eq!(Margins::from_str("10").unwrap().relative_total(), 0.4);
+        assert_eq!(Margins::from_str("400px,10").unwrap().relative_total(), 0.2);
+    }
+
+    #[test]
+    fn oversized_output_margins_do_not_overflow() {
+        let margins = Margins::from_str("60").unwrap();
+        let output = MarginBasis::output_dimension(&Size::new(100, 100), [&margins]);
+        let border = margins.to_border(output);
+        assert_eq!(border, Border::new(6000, 6000, 6000, 6000));
     }
 
     #[test]
@@ -153,32 +356,39 @@ mod tests {
     #[test]
     fn margins_to_border() {
         let margins = Margins::from_str("10").unwrap();
-        let size = Size::new(100, 200);
-        let border = margins.to_border_with_size(&size);
+        let dim = MarginBasis::default().dimension(&Size::new(100, 200));
+        let border = margins.to_border(dim);
         assert_eq!(border.top(), 15);
         assert_eq!(border.right(), 15);
         assert_eq!(border.bottom(), 15);
         assert_eq!(border.left(), 15);
 
         let margins = Margins::from_str("10 20").unwrap();
-        let border = margins.to_border_with_size(&size);
+        let border = margins.to_border(dim);
         assert_eq!(border.top(), 15);
         assert_eq!(border.right(), 30);
         assert_eq!(border.bottom(), 15);
         assert_eq!(border.left(), 30);
 
         let margins = Margins::from_str("10 20 30").unwrap();
-        let border = margins.to_border_with_size(&size);
+        let border = margins.to_border(dim);
         assert_eq!(border.top(), 15);
         assert_eq!(border.right(), 30);
         assert_eq!(border.bottom(), 45);
         assert_eq!(border.left(), 30);
 
         let margins = Margins::from_str("10 20 30 40").unwrap();
-        let border = margins.to_border_with_size(&size);
+        let border = margins.to_border(dim);
         assert_eq!(border.top(), 15);
         assert_eq!(border.right(), 30);
         assert_eq!(border.bottom(), 45);
         assert_eq!(border.left(), 60);
+
+        let margins = Margins::from_str("40px,5%").unwrap();
+        let border = margins.to_border(dim);
+        assert_eq!(border.top(), 40);
+        assert_eq!(border.right(), 8);
+        assert_eq!(border.bottom(), 40);
+        assert_eq!(border.left(), 8);
     }
 }
@@ -1,14 +1,26 @@
 //! Geometry module.
 mod aspect_ratio;
 mod border;
+mod corner_radius;
+mod corner_shape;
+mod focus_point;
+mod length;
 mod margins;
 mod point;
+mod radii;
 mod relative_position;
+mod side;
 mod size;
 
-pub use aspect_ratio::AspectRatio;
+pub use aspect_ratio::{AspectRatio, AspectRatioMode};
 pub use border::Border;
-pub use margins::Margins;
+pub use corner_radius::CornerRadius;
+pub use corner_shape::CornerShape;
+pub use focus_point::FocusPoint;
+pub use length::{Length, percentage};
+pub use margins::{MarginBasis, Margins};
 pub use point::Point;
+pub use radii::Radii;
 pub use relative_position::*;
+pub use side::Side;
 pub use size::Size;
@@ -2,7 +2,7 @@
 use derive_getters::Getters;
 
 /// A point is a location in a two-dimensional space.
-#[derive(Debug, Getters, PartialEq)]
+#[derive(Clone, Debug, Getters, PartialEq)]
 pub struct Point {
     /// The x-coordinate of the point.
     x: u32,
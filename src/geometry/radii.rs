@@ -0,0 +1,90 @@
+//! Radii are the specific pixel radii of the four corners of the image.
+use crate::geometry::CornerShape;
+use derive_getters::Getters;
+
+/// Radii are the specific pixel radii of the top-left, top-right,
+/// bottom-right and bottom-left corners of the image.
+///
+/// A radius of zero leaves that corner square, whatever the shape of the other corners.
+#[derive(Clone, Debug, Default, Getters, PartialEq)]
+pub struct Radii {
+    /// The radius of the top-left corner.
+    top_left: u32,
+    /// The radius of the top-right corner.
+    top_right: u32,
+    /// The radius of the bottom-right corner.
+    bottom_right: u32,
+    /// The radius of the bottom-left corner.
+    bottom_left: u32,
+    /// The curve used to cut every rounded corner.
+    shape: CornerShape,
+}
+
+impl Radii {
+    /// Create new radii with the given values.
+    pub fn new(top_left: u32, top_right: u32, bottom_right: u32, bottom_left: u32) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+            shape: CornerShape::default(),
+        }
+    }
+
+    /// Create radii with the same value for all four corners.
+    pub fn uniform(radius: u32) -> Self {
+        Self::new(radius, radius, radius, radius)
+    }
+
+    /// Cut every rounded corner with the given shape.
+    pub fn with_shape(mut self, shape: CornerShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Grow every rounded corner by the given distance, for a shape drawn concentrically around this one.
+    ///
+    /// Square corners stay square.
+    pub fn grow(&self, distance: u32) -> Self {
+        let grow = |radius: u32| if radius == 0 { 0 } else { radius + distance };
+        Self::new(
+            grow(self.top_left),
+            grow(self.top_right),
+            grow(self.bottom_right),
+            grow(self.bottom_left),
+        )
+        .with_shape(self.shape)
+    }
+
+    /// Whether all four corners are square.
+    pub fn is_square(&self) -> bool {
+        self.top_left == 0 && self.top_right == 0 && self.bottom_right == 0 && self.bottom_left == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow_radii() {
+        let radii = Radii::new(0, 5, 10, 0).grow(3);
+        assert_eq!(radii, Radii::new(0, 8, 13, 0));
+
+        let radii = Radii::uniform(4).with_shape(CornerShape::Chamfer).grow(2);
+        assert_eq!(radii.shape(), &CornerShape::Chamfer);
+    }
+
+    #[test]
+    fn square_radii() {
+        assert!(Radii::default().is_square());
+        assert!(Radii::uniform(0).is_square());
+        assert!(!Radii::new(0, 0, 1, 0).is_square());
+        assert!(
+            Radii::default()
+                .with_shape(CornerShape::Chamfer)
+                .is_square()
+        );
+    }
+}
@@ -11,7 +11,7 @@ use strum_macros::{AsRefStr, EnumIter, EnumString};
 /// - Horizontal position: left, center, right
 /// - Vertical position: top, center, bottom
 ///
-#[derive(Debug, Default, Getters, PartialEq)]
+#[derive(Clone, Debug, Default, Getters, PartialEq)]
 pub struct RelativePosition {
     /// Horizontal position of the image.
     horizontal: HorizontalPosition,
@@ -55,7 +55,7 @@ where
 }
 
 /// Horizontal position of an element in the final frame.
-#[derive(AsRefStr, Debug, Default, EnumIter, EnumString, PartialEq)]
+#[derive(AsRefStr, Clone, Debug, Default, EnumIter, EnumString, PartialEq)]
 #[strum(serialize_all = "snake_case")]
 pub enum HorizontalPosition {
     /// The element is positioned on the left side of the frame.
@@ -68,7 +68,7 @@ pub enum HorizontalPosition {
 }
 
 /// Vertical position of an element in the final frame.
-#[derive(AsRefStr, Debug, Default, EnumIter, EnumString, PartialEq)]
+#[derive(AsRefStr, Clone, Debug, Default, EnumIter, EnumString, PartialEq)]
 #[strum(serialize_all = "snake_case")]
 pub enum VerticalPosition {
     /// The element is positioned at the top of the frame.
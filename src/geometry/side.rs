@@ -0,0 +1,17 @@
+//! A side is one of the four edges of the image or frame.
+use strum_macros::{AsRefStr, EnumString};
+
+/// One of the four edges of the image or frame.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Side {
+    /// The top edge.
+    Top,
+    /// The right edge.
+    Right,
+    /// The bottom edge.
+    #[default]
+    Bottom,
+    /// The left edge.
+    Left,
+}
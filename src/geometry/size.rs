@@ -1,8 +1,12 @@
 //! A module for parsing and storing size values.
+use crate::errors::OliframeError;
 use derive_getters::Getters;
+use std::str::FromStr;
 
 /// A struct for storing size values.
-#[derive(Debug, Getters, PartialEq)]
+///
+/// The user can provide a size as the width and height in pixels, separated by an "x" (e.g.: `1080x1350`).
+#[derive(Clone, Debug, Getters, PartialEq)]
 pub struct Size {
     /// The distance from the left to right side.
     width: u32,
@@ -11,6 +15,9 @@ pub struct Size {
 }
 
 impl Size {
+    /// The largest side of an output image, as large as most image formats can store.
+    pub const MAX_SIDE: u32 = 65_535;
+
     /// Extract the width and height values as a tuple.
     pub fn dimensions(&self) -> (u32, u32) {
         (self.width, self.height)
@@ -31,3 +38,44 @@ impl From<(u32, u32)> for Size {
         }
     }
 }
+
+impl FromStr for Size {
+    type Err = OliframeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || OliframeError::InvalidInput(format!("Invalid size: {input}"));
+        let (width, height) = input.split_once(['x', 'X', '×']).ok_or_else(invalid)?;
+        let width = width.trim().parse::<u32>().map_err(|_| invalid())?;
+        let height = height.trim().parse::<u32>().map_err(|_| invalid())?;
+        if width == 0 || height == 0 {
+            return Err(OliframeError::InvalidInput(format!(
+                "Sizes cannot be empty: {input}"
+            )));
+        }
+
+        Ok(Self { width, height })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!("1080x1350".parse::<Size>().unwrap(), Size::new(1080, 1350));
+        assert_eq!(
+            "2048 X 2048".parse::<Size>().unwrap(),
+            Size::new(2048, 2048)
+        );
+        assert_eq!("1080×1920".parse::<Size>().unwrap(), Size::new(1080, 1920));
+    }
+
+    #[test]
+    fn invalid_sizes_are_err() {
+        assert!("1080".parse::<Size>().is_err());
+        assert!("1080x".parse::<Size>().is_err());
+        assert!("0x100".parse::<Size>().is_err());
+        assert!("-5x100".parse::<Size>().is_err());
+    }
+}
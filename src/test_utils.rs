@@ -1,13 +1,19 @@
+use crate::frame::{Antialias, Mask, Outline};
+use crate::geometry::{Point, Radii, Size};
 use fs_extra::dir;
 use fs_extra::dir::CopyOptions;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{ImageEncoder, RgbImage, Rgba, RgbaImage};
 use std::fs::create_dir_all;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::{Builder, NamedTempFile, TempDir};
 
 pub const TEST_FS_PREFIX: &str = "oliframe_test_";
 const HIDDEN_PREFIX: &str = ".oliframe_test_";
 const EXTENSIONS: [&str; 8] = ["jpg", "png", "gif", "jpeg", "bmp", "tiff", "txt", "pdf"];
 const SAMPLE_DIR: &str = "images/test";
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
 
 pub fn populate_test_directory(base_dir: &TempDir) -> Vec<NamedTempFile> {
     let mut handles = Vec::new();
@@ -43,3 +49,65 @@ pub fn populate_test_directory(base_dir: &TempDir) -> Vec<NamedTempFile> {
 
     handles
 }
+
+pub fn save_jpeg_with_orientation(path: &Path, pixels: &RgbImage, orientation: u16) {
+    let mut jpeg = Vec::new();
+    JpegEncoder::new(&mut jpeg).encode_image(pixels).unwrap();
+
+    // A big-endian TIFF header with a single IFD holding the orientation tag.
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+    exif.extend_from_slice(&orientation.to_be_bytes());
+    exif.extend_from_slice(&[0; 6]);
+    let mut app1 = vec![0xff, 0xe1];
+    app1.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+    app1.extend_from_slice(&exif);
+
+    // The segment goes right after the start-of-image marker.
+    jpeg.splice(2..2, app1);
+    std::fs::write(path, jpeg).unwrap();
+}
+
+pub fn save_png_with_icc_profile(path: &Path, pixels: &RgbaImage, icc: Vec<u8>) {
+    let mut png = Vec::new();
+    let mut encoder = PngEncoder::new(&mut png);
+    encoder.set_icc_profile(icc).unwrap();
+    encoder
+        .write_image(
+            pixels,
+            pixels.width(),
+            pixels.height(),
+            image::ExtendedColorType::Rgba8,
+        )
+        .unwrap();
+    std::fs::write(path, png).unwrap();
+}
+
+/// The outline of an image window with the given position, size and corner radii.
+pub fn window(position: Point, size: Size, radii: Radii) -> Outline {
+    Outline::new(position, size, radii, Mask::default(), Antialias::default())
+}
+
+/// Paint onto a white 140×120 canvas around a 100×60 image window at (20, 20),
+/// returning the painted pixels.
+pub fn paint_around_window(paint: impl FnOnce(&mut RgbaImage, &Outline)) -> Vec<(u32, u32)> {
+    let mut canvas = RgbaImage::from_pixel(140, 120, WHITE);
+    paint(
+        &mut canvas,
+        &window(Point::new(20, 20), Size::new(100, 60), Radii::default()),
+    );
+    canvas
+        .enumerate_pixels()
+        .filter(|(_, _, p)| **p != WHITE)
+        .map(|(x, y, _)| (x, y))
+        .collect()
+}
+
+/// The bounds (left, top, right, bottom) of the given pixels.
+pub fn bounds(pixels: &[(u32, u32)]) -> (u32, u32, u32, u32) {
+    (
+        pixels.iter().map(|(x, _)| *x).min().unwrap(),
+        pixels.iter().map(|(_, y)| *y).min().unwrap(),
+        pixels.iter().map(|(x, _)| *x).max().unwrap(),
+        pixels.iter().map(|(_, y)| *y).max().unwrap(),
+    )
+}